@@ -36,6 +36,7 @@ export async function fetchAndUpdateOracle(symbol: string) {
 }
 
 export async function openFinancing(params: {
+  positionIndex: number;
   collateralMint: anchor.web3.PublicKey;
   collateralAmount: number;
   collateralUsd: number;
@@ -49,7 +50,7 @@ export async function openFinancing(params: {
   liquidationThreshold: number;
   oracleSources: anchor.web3.PublicKey[];
 }) {
-  const [state] = pdas.financing(provider.wallet.publicKey, params.collateralMint);
+  const [state] = pdas.financing(provider.wallet.publicKey, params.positionIndex);
   await financingProgram.methods
     .initializeFinancing(
       new anchor.BN(params.collateralAmount),
@@ -115,6 +116,7 @@ export async function settle(state: anchor.web3.PublicKey, obligations: number,
 export async function exampleFlow(collateralMint: anchor.web3.PublicKey) {
   await fetchAndUpdateOracle("USDC-USD");
   const state = await openFinancing({
+    positionIndex: 0,
     collateralMint,
     collateralAmount: 1_000,
     collateralUsd: 100_000,
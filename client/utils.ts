@@ -16,11 +16,15 @@ export const ENDPOINTS = {
 };
 
 export const pdas = {
-  financing: (user: PublicKey, collateralMint: PublicKey) =>
-    PublicKey.findProgramAddressSync(
-      [Buffer.from("financing"), user.toBuffer(), collateralMint.toBuffer()],
+  // Positions are keyed by [b"financing", user, position_index (u64 LE)], see derive_position_pda
+  financing: (user: PublicKey, positionIndex: number | bigint) => {
+    const index = Buffer.alloc(8);
+    index.writeBigUInt64LE(BigInt(positionIndex));
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("financing"), user.toBuffer(), index],
       PROGRAM_IDS.financing
-    ),
+    );
+  },
   liquidationAuthority: (owner: PublicKey) =>
     PublicKey.findProgramAddressSync([Buffer.from("liquidation"), owner.toBuffer()], PROGRAM_IDS.liquidation),
   vault: () => PublicKey.findProgramAddressSync([Buffer.from("vault")], PROGRAM_IDS.lpVault),
@@ -0,0 +1,59 @@
+//! Shared helpers for the `migrate_*_version` instructions.
+//!
+//! Accounts created before a program's state carried a `version` byte were allocated for
+//! that original layout, which lacks every field appended since. They are decoded with the
+//! original layout and upgraded by the owning program, which gives each added field an
+//! explicit value, then resized in place and rewritten in the current layout.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// Decode a `T` from `info`. An account of exactly `space` bytes is read in the current
+/// layout; any other size is read as the pre-version layout `L` (behind the same
+/// discriminator) and converted with `upgrade`. The original layout isn't always the
+/// shorter one, so the size must match exactly.
+pub fn load_any_layout<T, L>(
+    info: &AccountInfo,
+    program_id: &Pubkey,
+    space: usize,
+    upgrade: impl FnOnce(L) -> T,
+) -> Result<T>
+where
+    T: AccountDeserialize + Discriminator,
+    L: AnchorDeserialize,
+{
+    require_keys_eq!(*info.owner, *program_id, ErrorCode::AccountOwnedByWrongProgram);
+    let data = info.try_borrow_data()?;
+    require!(data.starts_with(T::DISCRIMINATOR), ErrorCode::AccountDiscriminatorMismatch);
+    if data.len() == space {
+        return T::try_deserialize(&mut &data[..]);
+    }
+    let legacy = L::deserialize(&mut &data[T::DISCRIMINATOR.len()..])
+        .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+    Ok(upgrade(legacy))
+}
+
+/// Resize `info` to `space` (topping its rent up from `payer` when it grows) and write
+/// `state` over it
+pub fn store_migrated<'info, T: AccountSerialize>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    state: &T,
+) -> Result<()> {
+    if info.data_len() != space {
+        let rent_due = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+        if rent_due > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer { from: payer.clone(), to: info.clone() },
+                ),
+                rent_due,
+            )?;
+        }
+        info.realloc(space, false)?;
+    }
+    state.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
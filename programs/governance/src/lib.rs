@@ -1,8 +1,29 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use layout_migration::{load_any_layout, store_migrated};
 
 declare_id!("Govr1111111111111111111111111111111111111111");
 
+/// Seed of the PDA that signs pause CPIs. Each pausable program's admin must be
+/// set to this address for governance pause proposals to execute.
+pub const GOVERNANCE_AUTHORITY_SEED: &[u8] = b"governance_authority";
+
+/// Seed of a voter's XGT stake account (also the authority of its stake vault ATA)
+pub const XGT_STAKE_SEED: &[u8] = b"xgt_stake";
+
+/// Longest stake lock (~1 year of 400ms slots); a max-length lock doubles voting weight
+pub const MAX_STAKE_LOCK_SLOTS: u64 = 78_840_000;
+
+/// Target slot time, used to convert the voting period (seconds) into slots
+pub const SLOT_DURATION_MS: u64 = 400;
+
+/// Quorum multiplier of 1x; per-action multipliers can only raise the quorum from here
+pub const QUORUM_MULTIPLIER_BASE_BPS: u64 = 10_000;
+
+/// GovernanceConfig layout version; bump with any change to its fields
+pub const STATE_VERSION: u8 = 1;
+
 #[program]
 pub mod governance {
     use super::*;
@@ -36,8 +57,13 @@ pub mod governance {
         config.proposal_count = 0;
         config.admin_authority = admin_authority;
         config.paused = false;  // Start unpaused
+        config.quorum_multiplier_bps = [QUORUM_MULTIPLIER_BASE_BPS; ProposalActionKind::COUNT];
+        config.xgt_mint = ctx.accounts.xgt_mint.key();
+        config.proposal_threshold = 0;  // Open proposals until the admin sets a threshold
+        config.version = STATE_VERSION;
 
         msg!("✅ Governance initialized:");
+        msg!("  XGT mint: {}", config.xgt_mint);
         msg!("  Quorum: {} votes", quorum_votes);
         msg!("  Voting period: {} seconds", voting_period);
         msg!("  Timelock delay: {} seconds", timelock_delay);
@@ -60,6 +86,7 @@ pub mod governance {
         title: String,
         description: String,
         eta: i64,
+        action: ProposalAction,
     ) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
         let config = &mut ctx.accounts.governance_config;
@@ -68,6 +95,15 @@ pub mod governance {
         require!(!config.paused, GovernanceError::GovernancePaused);
         // ========== END CIRCUIT BREAKER CHECK ==========
 
+        // ========== PROPOSAL THRESHOLD ==========
+        // Proposers need locked XGT at stake, so spamming proposals isn't free
+        let clock = Clock::get()?;
+        require!(
+            config.meets_proposal_threshold(ctx.accounts.creator_stake.as_deref(), clock.slot),
+            GovernanceError::BelowProposalThreshold
+        );
+        // ========== END PROPOSAL THRESHOLD ==========
+
         // ========== SECURITY FIX (VULN-060): INCREMENT PROPOSAL COUNT ==========
         // Each proposal gets a unique nonce to prevent seed collision
         config.proposal_count = config.proposal_count.saturating_add(1);
@@ -81,16 +117,21 @@ pub mod governance {
         proposal.for_votes = 0;
         proposal.against_votes = 0;
         proposal.timelock_eta = eta;
+        proposal.voting_end_slot = clock.slot
+            .checked_add(config.voting_period_slots().ok_or(GovernanceError::InvalidVotingPeriod)?)
+            .ok_or(GovernanceError::InvalidVotingPeriod)?;
         proposal.executed = false;
+        proposal.action = action;
 
         // Emit event for monitoring
-        let clock = Clock::get()?;
         emit!(ProposalCreated {
             proposal_id: ctx.accounts.proposal.key(),
             creator: ctx.accounts.creator.key(),
             nonce: proposal_nonce,
             title,
             timelock_eta: eta,
+            voting_end_slot: ctx.accounts.proposal.voting_end_slot,
+            action,
             timestamp: clock.unix_timestamp,
         });
 
@@ -110,14 +151,23 @@ pub mod governance {
 
         // ========== SECURITY FIX (VULN-057): VALIDATE VOTE WEIGHT ==========
 
-        // Get actual token balance from user's token account
-        let user_token_account = &ctx.accounts.user_xgt_account;
-        let weight = user_token_account.amount;
+        // Voting power is the lock-boosted weight of the voter's XGT stake;
+        // an expired lock counts for nothing since the tokens could be withdrawn
+        let clock = Clock::get()?;
+        require!(clock.slot < proposal.voting_end_slot, GovernanceError::VotingClosed);
+        let weight = ctx.accounts.stake.voting_weight(clock.slot);
 
         // Ensure user has voting power
         require!(weight > 0, GovernanceError::NoVotingPower);
 
-        msg!("✅ Vote weight validated: {} XGT tokens", weight);
+        // The stake must stay locked until voting ends, otherwise the same XGT could
+        // vote, unstake and be re-staked from another wallet to vote again
+        require!(
+            ctx.accounts.stake.unlock_slot >= proposal.voting_end_slot,
+            GovernanceError::StakeUnlocksBeforeVotingEnd
+        );
+
+        msg!("✅ Vote weight validated: {} (staked {} XGT)", weight, ctx.accounts.stake.amount);
 
         // ========== END SECURITY FIX ==========
 
@@ -135,7 +185,6 @@ pub mod governance {
         msg!("Vote recorded: {} with {} XGT", if support { "FOR" } else { "AGAINST" }, weight);
 
         // Emit event for monitoring
-        let clock = Clock::get()?;
         let for_votes = proposal.for_votes;
         let against_votes = proposal.against_votes;
         let proposal_id = ctx.accounts.proposal.key();
@@ -161,9 +210,12 @@ pub mod governance {
 
         // ========== SECURITY FIX (VULN-058): ADD QUORUM THRESHOLD ==========
 
-        // Check for_votes meets quorum AND exceeds against_votes
+        // Check for_votes meets the action's (possibly escalated) quorum AND exceeds against_votes
+        let required_quorum = config
+            .required_quorum(&proposal.action)
+            .ok_or(GovernanceError::InvalidQuorumMultiplier)?;
         require!(
-            proposal.for_votes >= config.quorum_votes,
+            proposal.for_votes >= required_quorum,
             GovernanceError::QuorumNotReached
         );
 
@@ -172,7 +224,7 @@ pub mod governance {
             GovernanceError::ProposalRejected
         );
 
-        msg!("✅ Quorum reached: {} votes (required: {})", proposal.for_votes, config.quorum_votes);
+        msg!("✅ Quorum reached: {} votes (required: {})", proposal.for_votes, required_quorum);
 
         // ========== END SECURITY FIX ==========
 
@@ -190,7 +242,7 @@ pub mod governance {
         Ok(())
     }
 
-    pub fn execute(ctx: Context<ExecuteProposal>) -> Result<()> {
+    pub fn execute<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
         let config = &ctx.accounts.governance_config;
         let clock = Clock::get()?;
@@ -200,9 +252,12 @@ pub mod governance {
 
         // ========== SECURITY FIX (VULN-058): ADD QUORUM THRESHOLD ==========
 
-        // Check for_votes meets quorum AND exceeds against_votes
+        // Check for_votes meets the action's (possibly escalated) quorum AND exceeds against_votes
+        let required_quorum = config
+            .required_quorum(&proposal.action)
+            .ok_or(GovernanceError::InvalidQuorumMultiplier)?;
         require!(
-            proposal.for_votes >= config.quorum_votes,
+            proposal.for_votes >= required_quorum,
             GovernanceError::QuorumNotReached
         );
 
@@ -224,12 +279,76 @@ pub mod governance {
         // ========== END SECURITY FIX ==========
 
         proposal.executed = true;
-        msg!("✅ Proposal executed successfully");
-
-        // Emit event for monitoring
+        let action = proposal.action;
         let for_votes = proposal.for_votes;
         let against_votes = proposal.against_votes;
         let proposal_id = ctx.accounts.proposal.key();
+
+        // ========== PROPOSAL ACTION DISPATCH ==========
+        let authority_bump = ctx.bumps.governance_authority;
+        let seeds = &[GOVERNANCE_AUTHORITY_SEED, &[authority_bump]];
+        let signer_seeds = &[&seeds[..]];
+        let authority = ctx.accounts.governance_authority.to_account_info();
+
+        match action {
+            ProposalAction::Signal => {}
+            ProposalAction::Pause { target } => {
+                let programs = target.programs();
+
+                // remaining_accounts: [program, pause state] per covered program, in programs() order
+                require!(
+                    ctx.remaining_accounts.len() == programs.len() * 2,
+                    GovernanceError::InvalidPauseTargetAccounts
+                );
+
+                for (program, accounts) in programs.iter().zip(ctx.remaining_accounts.chunks(2)) {
+                    pause_program(*program, &accounts[0], &accounts[1], &authority, signer_seeds)?;
+                    msg!("🛑 {:?} paused by governance proposal {}", program, proposal_id);
+                }
+
+                emit!(GovernancePauseExecuted {
+                    proposal_id,
+                    target,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            ProposalAction::DeprecateOracle { oracle } => {
+                // remaining_accounts: [financing_engine program, protocol config]
+                require!(
+                    ctx.remaining_accounts.len() == 2,
+                    GovernanceError::InvalidProposalAccounts
+                );
+                require_keys_eq!(
+                    ctx.remaining_accounts[0].key(),
+                    financing_engine::ID,
+                    GovernanceError::InvalidProposalAccounts
+                );
+
+                financing_engine::cpi::deprecate_oracle(
+                    CpiContext::new_with_signer(
+                        ctx.remaining_accounts[0].clone(),
+                        financing_engine::cpi::accounts::AdminProtocolAction {
+                            protocol_config: ctx.remaining_accounts[1].clone(),
+                            admin_authority: authority.clone(),
+                        },
+                        signer_seeds,
+                    ),
+                    oracle,
+                )?;
+                msg!("🛑 Oracle {} deprecated by governance proposal {}", oracle, proposal_id);
+
+                emit!(GovernanceOracleDeprecated {
+                    proposal_id,
+                    oracle,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+        }
+        // ========== END PROPOSAL ACTION DISPATCH ==========
+
+        msg!("✅ Proposal executed successfully");
+
+        // Emit event for monitoring
         emit!(ProposalExecuted {
             proposal_id,
             executor: ctx.accounts.executor.key(),
@@ -241,6 +360,158 @@ pub mod governance {
         Ok(())
     }
 
+    // ========== STAKED GOVERNANCE ==========
+    /// Lock XGT for `lock_slots` in exchange for boosted voting weight:
+    /// weight = amount * (1 + lock_slots / MAX_STAKE_LOCK_SLOTS)
+    pub fn stake_xgt(ctx: Context<StakeXgt>, amount: u64, lock_slots: u64) -> Result<()> {
+        require!(!ctx.accounts.governance_config.paused, GovernanceError::GovernancePaused);
+        require!(amount > 0, GovernanceError::InvalidStakeAmount);
+        require!(
+            lock_slots > 0 && lock_slots <= MAX_STAKE_LOCK_SLOTS,
+            GovernanceError::InvalidLockDuration
+        );
+
+        let stake = &mut ctx.accounts.stake;
+        require!(stake.amount == 0, GovernanceError::StakeAlreadyActive);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_xgt_account.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let clock = Clock::get()?;
+        let weight = boosted_weight(amount, lock_slots).ok_or(GovernanceError::InvalidWeight)?;
+        stake.owner = ctx.accounts.owner.key();
+        stake.xgt_mint = ctx.accounts.xgt_mint.key();
+        stake.amount = amount;
+        stake.weight = weight;
+        stake.unlock_slot = clock.slot
+            .checked_add(lock_slots)
+            .ok_or(GovernanceError::InvalidLockDuration)?;
+
+        msg!("🔒 Staked {} XGT for {} slots: voting weight {}", amount, lock_slots, weight);
+
+        emit!(XgtStaked {
+            owner: stake.owner,
+            amount,
+            lock_slots,
+            weight,
+            unlock_slot: stake.unlock_slot,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw staked XGT once its lock has expired
+    pub fn unstake_xgt(ctx: Context<UnstakeXgt>) -> Result<()> {
+        let clock = Clock::get()?;
+        let stake = &ctx.accounts.stake;
+        require!(stake.amount > 0, GovernanceError::NoActiveStake);
+        require!(clock.slot >= stake.unlock_slot, GovernanceError::StakeLocked);
+
+        let amount = stake.amount;
+        let owner = stake.owner;
+        let stake_bump = ctx.bumps.stake;
+        let seeds = &[XGT_STAKE_SEED, owner.as_ref(), &[stake_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    to: ctx.accounts.user_xgt_account.to_account_info(),
+                    authority: ctx.accounts.stake.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        let stake = &mut ctx.accounts.stake;
+        stake.amount = 0;
+        stake.weight = 0;
+        stake.unlock_slot = 0;
+
+        msg!("🔓 Unstaked {} XGT", amount);
+
+        emit!(XgtUnstaked {
+            owner,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+    // ========== END STAKED GOVERNANCE ==========
+
+    /// Set the quorum multiplier for one proposal action type (admin only).
+    /// High-impact actions can require more than the base quorum_votes to pass.
+    pub fn set_quorum_multiplier(
+        ctx: Context<AdminGovernanceAction>,
+        action_kind: ProposalActionKind,
+        multiplier_bps: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.governance_config;
+
+        // Validate admin authority
+        require!(
+            ctx.accounts.admin_authority.key() == config.admin_authority,
+            GovernanceError::Unauthorized
+        );
+        require!(
+            multiplier_bps >= QUORUM_MULTIPLIER_BASE_BPS,
+            GovernanceError::InvalidQuorumMultiplier
+        );
+
+        config.quorum_multiplier_bps[action_kind as usize] = multiplier_bps;
+        msg!("✅ Quorum multiplier for {:?} set to {}bps", action_kind, multiplier_bps);
+
+        let clock = Clock::get()?;
+        emit!(QuorumMultiplierUpdated {
+            admin: ctx.accounts.admin_authority.key(),
+            action_kind,
+            multiplier_bps,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the locked XGT a creator must hold to open a proposal (admin only). 0 = no threshold
+    pub fn set_proposal_threshold(
+        ctx: Context<AdminGovernanceAction>,
+        proposal_threshold: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.governance_config;
+
+        // Validate admin authority
+        require!(
+            ctx.accounts.admin_authority.key() == config.admin_authority,
+            GovernanceError::Unauthorized
+        );
+
+        config.proposal_threshold = proposal_threshold;
+        msg!("✅ Proposal threshold set to {} XGT", proposal_threshold);
+
+        let clock = Clock::get()?;
+        emit!(ProposalThresholdUpdated {
+            admin: ctx.accounts.admin_authority.key(),
+            proposal_threshold,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     // ========== MEDIUM-SEVERITY FIX (VULN-020): CIRCUIT BREAKER ==========
     /// Pause governance (admin only)
     pub fn pause_governance(ctx: Context<AdminGovernanceAction>) -> Result<()> {
@@ -292,6 +563,138 @@ pub mod governance {
         Ok(())
     }
     // ========== END CIRCUIT BREAKER ==========
+
+    /// Rewrite the governance config in the current layout stamped with STATE_VERSION. A
+    /// config created before the version byte existed is decoded in that layout and grown,
+    /// with explicit defaults for every field added since and `xgt_mint` as its XGT mint.
+    pub fn migrate_governance_version(ctx: Context<MigrateGovernanceVersion>) -> Result<()> {
+        let config_info = ctx.accounts.governance_config.to_account_info();
+        let space = 8 + GovernanceConfig::LEN;
+        let xgt_mint = ctx.accounts.xgt_mint.key();
+        let mut config: GovernanceConfig = load_any_layout(&config_info, &crate::ID, space, |legacy| {
+            GovernanceConfig::from_legacy(legacy, xgt_mint)
+        })?;
+
+        require!(
+            ctx.accounts.admin_authority.key() == config.admin_authority,
+            GovernanceError::Unauthorized
+        );
+
+        let from_version = config.version;
+        // An account written by a newer program can't be downgraded
+        require!(from_version <= STATE_VERSION, GovernanceError::VersionMismatch);
+        config.version = STATE_VERSION;
+        store_migrated(
+            &config_info,
+            &ctx.accounts.admin_authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            space,
+            &config,
+        )?;
+        msg!("✅ Governance config migrated from version {} to {}", from_version, STATE_VERSION);
+
+        let clock = Clock::get()?;
+        emit!(GovernanceVersionMigrated {
+            from_version,
+            to_version: STATE_VERSION,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+// ========== LAYOUT MIGRATION ==========
+/// GovernanceConfig as it was written before the layout carried a version byte
+#[derive(AnchorDeserialize)]
+struct LegacyGovernanceConfig {
+    quorum_votes: u64,
+    voting_period: i64,
+    timelock_delay: i64,
+    proposal_count: u64,
+    admin_authority: Pubkey,
+    paused: bool,
+}
+
+impl GovernanceConfig {
+    /// Carry a pre-version config over with initialize_governance's defaults for every field
+    /// added since. The XGT mint can't be defaulted, so the migrating admin supplies it.
+    fn from_legacy(legacy: LegacyGovernanceConfig, xgt_mint: Pubkey) -> Self {
+        Self {
+            quorum_votes: legacy.quorum_votes,
+            voting_period: legacy.voting_period,
+            timelock_delay: legacy.timelock_delay,
+            proposal_count: legacy.proposal_count,
+            admin_authority: legacy.admin_authority,
+            paused: legacy.paused,
+            quorum_multiplier_bps: [QUORUM_MULTIPLIER_BASE_BPS; ProposalActionKind::COUNT],
+            xgt_mint,
+            proposal_threshold: 0,
+            version: 0,
+        }
+    }
+}
+
+// ========== TARGETED PAUSE CPI ==========
+/// CPI a single program's admin pause instruction, signed by the governance authority PDA.
+/// The callee validates its own state PDA and that the authority is its admin.
+fn pause_program<'info>(
+    target: PauseTarget,
+    program: &AccountInfo<'info>,
+    state: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    require_keys_eq!(
+        program.key(),
+        target.program_id().ok_or(GovernanceError::InvalidPauseTargetAccounts)?,
+        GovernanceError::InvalidPauseTargetAccounts
+    );
+
+    match target {
+        PauseTarget::Financing => financing_engine::cpi::pause_protocol(CpiContext::new_with_signer(
+            program.clone(),
+            financing_engine::cpi::accounts::AdminProtocolAction {
+                protocol_config: state.clone(),
+                admin_authority: authority.clone(),
+            },
+            signer_seeds,
+        )),
+        PauseTarget::Vault => lp_vault::cpi::pause_vault(CpiContext::new_with_signer(
+            program.clone(),
+            lp_vault::cpi::accounts::AdminVaultAction {
+                vault: state.clone(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        )),
+        PauseTarget::Oracle => oracle_framework::cpi::pause_oracle(CpiContext::new_with_signer(
+            program.clone(),
+            oracle_framework::cpi::accounts::AdminOracleAction {
+                oracle: state.clone(),
+                protocol_admin: authority.clone(),
+            },
+            signer_seeds,
+        )),
+        PauseTarget::Treasury => treasury_engine::cpi::pause_treasury(CpiContext::new_with_signer(
+            program.clone(),
+            treasury_engine::cpi::accounts::AdminTreasuryAction {
+                treasury: state.clone(),
+                admin_authority: authority.clone(),
+            },
+            signer_seeds,
+        )),
+        PauseTarget::All => err!(GovernanceError::InvalidPauseTargetAccounts),
+    }
+}
+// ========== END TARGETED PAUSE CPI ==========
+
+/// Voting weight of `amount` XGT locked for `lock_slots`: linear boost from 1x (no lock)
+/// to 2x at MAX_STAKE_LOCK_SLOTS. Longer locks are clamped to the maximum.
+pub fn boosted_weight(amount: u64, lock_slots: u64) -> Option<u64> {
+    let lock = lock_slots.min(MAX_STAKE_LOCK_SLOTS) as u128;
+    let boost = (amount as u128).checked_mul(lock)? / MAX_STAKE_LOCK_SLOTS as u128;
+    u64::try_from((amount as u128).checked_add(boost)?).ok()
 }
 
 #[derive(Accounts)]
@@ -308,11 +711,21 @@ pub struct CreateProposal<'info> {
     pub proposal: Account<'info, Proposal>,
     // ========== END SECURITY FIX (VULN-060) ==========
 
-    #[account(mut, seeds = [b"governance_config"], bump)]
+    #[account(
+        mut,
+        seeds = [b"governance_config"],
+        bump,
+        constraint = governance_config.version == STATE_VERSION @ GovernanceError::VersionMismatch
+    )]
     pub governance_config: Account<'info, GovernanceConfig>,
 
     #[account(mut)]
     pub creator: Signer<'info>,
+
+    /// Creator's XGT stake, checked against proposal_threshold; may be omitted while the threshold is 0
+    #[account(seeds = [XGT_STAKE_SEED, creator.key().as_ref()], bump)]
+    pub creator_stake: Option<Account<'info, XgtStake>>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -327,6 +740,9 @@ pub struct InitializeGovernance<'info> {
     )]
     pub governance_config: Account<'info, GovernanceConfig>,
 
+    /// Canonical XGT mint; only stakes of this token carry voting power
+    pub xgt_mint: Account<'info, Mint>,
+
     #[account(mut)]
     pub payer: Signer<'info>,
 
@@ -356,14 +772,18 @@ pub struct Vote<'info> {
     #[account(mut)]
     pub voter: Signer<'info>,
 
-    /// User's XGT token account (voting power comes from balance)
+    /// Voter's XGT stake (voting power comes from its lock-boosted weight)
     #[account(
-        constraint = user_xgt_account.owner == voter.key(),
-        constraint = user_xgt_account.mint == xgt_mint.key()
+        seeds = [XGT_STAKE_SEED, voter.key().as_ref()],
+        bump,
+        constraint = stake.xgt_mint == xgt_mint.key()
     )]
-    pub user_xgt_account: Account<'info, TokenAccount>,
+    pub stake: Account<'info, XgtStake>,
 
-    /// CHECK: XGT governance token mint
+    /// CHECK: XGT governance token mint; must be the canonical mint recorded at init
+    #[account(
+        constraint = xgt_mint.key() == governance_config.xgt_mint @ GovernanceError::InvalidGovernanceToken
+    )]
     pub xgt_mint: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
@@ -404,6 +824,81 @@ pub struct ExecuteProposal<'info> {
 
     /// Executor (must sign to execute)
     pub executor: Signer<'info>,
+
+    /// CHECK: PDA that signs pause CPIs as the target programs' admin
+    #[account(seeds = [GOVERNANCE_AUTHORITY_SEED], bump)]
+    pub governance_authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StakeXgt<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + XgtStake::LEN,
+        seeds = [XGT_STAKE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, XgtStake>,
+
+    /// Stake vault holding the locked XGT, owned by the stake PDA
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = xgt_mint,
+        associated_token::authority = stake
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_xgt_account.owner == owner.key(),
+        constraint = user_xgt_account.mint == xgt_mint.key()
+    )]
+    pub user_xgt_account: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = xgt_mint.key() == governance_config.xgt_mint @ GovernanceError::InvalidGovernanceToken
+    )]
+    pub xgt_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"governance_config"], bump)]
+    pub governance_config: Account<'info, GovernanceConfig>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeXgt<'info> {
+    #[account(
+        mut,
+        seeds = [XGT_STAKE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, XgtStake>,
+
+    #[account(
+        mut,
+        associated_token::mint = stake.xgt_mint,
+        associated_token::authority = stake
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_xgt_account.owner == owner.key(),
+        constraint = user_xgt_account.mint == stake.xgt_mint
+    )]
+    pub user_xgt_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
 }
 
 // ========== MEDIUM-SEVERITY FIX (VULN-020): CIRCUIT BREAKER ACCOUNTS ==========
@@ -412,7 +907,8 @@ pub struct AdminGovernanceAction<'info> {
     #[account(
         mut,
         seeds = [b"governance_config"],
-        bump
+        bump,
+        constraint = governance_config.version == STATE_VERSION @ GovernanceError::VersionMismatch
     )]
     pub governance_config: Account<'info, GovernanceConfig>,
 
@@ -421,6 +917,26 @@ pub struct AdminGovernanceAction<'info> {
 }
 // ========== END CIRCUIT BREAKER ACCOUNTS ==========
 
+#[derive(Accounts)]
+pub struct MigrateGovernanceVersion<'info> {
+    /// CHECK: Governance config PDA in either layout; owner and discriminator are checked by the handler
+    #[account(
+        mut,
+        seeds = [b"governance_config"],
+        bump
+    )]
+    pub governance_config: UncheckedAccount<'info>,
+
+    /// Admin authority (must match governance_config.admin_authority); pays for the added fields
+    #[account(mut)]
+    pub admin_authority: Signer<'info>,
+
+    /// Canonical XGT mint recorded on a pre-version config; unused once xgt_mint is set
+    pub xgt_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct GovernanceConfig {
     pub quorum_votes: u64,
@@ -429,10 +945,34 @@ pub struct GovernanceConfig {
     pub proposal_count: u64,
     pub admin_authority: Pubkey,  // Added for circuit breaker admin
     pub paused: bool,  // CIRCUIT BREAKER (VULN-020)
+    pub quorum_multiplier_bps: [u64; ProposalActionKind::COUNT],  // Per-action quorum escalation, indexed by ProposalActionKind
+    pub xgt_mint: Pubkey,  // Canonical governance token; stakes of any other mint can't vote
+    pub proposal_threshold: u64,  // Locked XGT a creator needs to open a proposal; 0 = open
+    pub version: u8,
 }
 
 impl GovernanceConfig {
-    pub const LEN: usize = 8 + 8 + 8 + 8 + 32 + 1;  // 4 u64s + 1 Pubkey + 1 bool
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 32 + 1 + 8 * ProposalActionKind::COUNT + 32 + 8 + 1;  // 4 u64s + 1 Pubkey + 1 bool + quorum multipliers + xgt_mint + proposal_threshold + version
+
+    /// Whether a creator with `stake` may open a proposal at `slot`
+    pub fn meets_proposal_threshold(&self, stake: Option<&XgtStake>, slot: u64) -> bool {
+        let locked = stake.map_or(0, |stake| stake.locked_amount(slot));
+        locked >= self.proposal_threshold
+    }
+
+    /// Votes a proposal with `action` needs: quorum_votes scaled by the action's multiplier
+    pub fn required_quorum(&self, action: &ProposalAction) -> Option<u64> {
+        let multiplier = self.quorum_multiplier_bps[action.kind() as usize].max(QUORUM_MULTIPLIER_BASE_BPS);
+        let required = (self.quorum_votes as u128).checked_mul(multiplier as u128)?
+            / QUORUM_MULTIPLIER_BASE_BPS as u128;
+        u64::try_from(required).ok()
+    }
+
+    /// Voting period converted from seconds to slots
+    pub fn voting_period_slots(&self) -> Option<u64> {
+        let seconds = u64::try_from(self.voting_period).ok()?;
+        seconds.checked_mul(1_000).map(|ms| ms / SLOT_DURATION_MS)
+    }
 }
 
 #[account]
@@ -444,11 +984,91 @@ pub struct Proposal {
     pub for_votes: u64,
     pub against_votes: u64,
     pub timelock_eta: i64,
+    pub voting_end_slot: u64,  // Last slot (exclusive) votes are accepted; stakes must stay locked until then
     pub executed: bool,
+    pub action: ProposalAction,
 }
 
 impl Proposal {
-    pub const LEN: usize = 32 + 8 + 4 + 128 + 4 + 256 + 8 + 8 + 8 + 1;
+    pub const MAX_TITLE_LEN: usize = 128;
+    pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+    pub const LEN: usize = 32 + 8 + 4 + Self::MAX_TITLE_LEN + 4 + Self::MAX_DESCRIPTION_LEN + 8 + 8 + 8 + 8 + 1
+        + ProposalAction::LEN;
+}
+
+/// What a proposal does on-chain once executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalAction {
+    /// Signalling vote only; execution just marks the proposal executed
+    Signal,
+    /// Pause one program, or every pausable program, via CPI
+    Pause { target: PauseTarget },
+    /// Add an oracle to the financing engine's protocol-wide denylist via CPI
+    DeprecateOracle { oracle: Pubkey },
+}
+
+impl ProposalAction {
+    pub const LEN: usize = 1 + 32; // variant tag + largest payload (DeprecateOracle pubkey)
+
+    pub fn kind(&self) -> ProposalActionKind {
+        match self {
+            ProposalAction::Signal => ProposalActionKind::Signal,
+            ProposalAction::Pause { .. } => ProposalActionKind::Pause,
+            ProposalAction::DeprecateOracle { .. } => ProposalActionKind::DeprecateOracle,
+        }
+    }
+}
+
+/// Proposal action type without its payload; keys the per-action quorum multipliers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalActionKind {
+    Signal,
+    Pause,
+    DeprecateOracle,
+}
+
+impl ProposalActionKind {
+    pub const COUNT: usize = 3;
+}
+
+/// Which program(s) a governance pause proposal halts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseTarget {
+    Financing,
+    Vault,
+    Oracle,
+    Treasury,
+    All,
+}
+
+impl PauseTarget {
+    /// Programs covered by this target, in the order their accounts are expected
+    pub fn programs(&self) -> &'static [PauseTarget] {
+        match self {
+            PauseTarget::Financing => &[PauseTarget::Financing],
+            PauseTarget::Vault => &[PauseTarget::Vault],
+            PauseTarget::Oracle => &[PauseTarget::Oracle],
+            PauseTarget::Treasury => &[PauseTarget::Treasury],
+            PauseTarget::All => &[
+                PauseTarget::Financing,
+                PauseTarget::Vault,
+                PauseTarget::Oracle,
+                PauseTarget::Treasury,
+            ],
+        }
+    }
+
+    /// Program id for a single-program target (None for All)
+    pub fn program_id(&self) -> Option<Pubkey> {
+        match self {
+            PauseTarget::Financing => Some(financing_engine::ID),
+            PauseTarget::Vault => Some(lp_vault::ID),
+            PauseTarget::Oracle => Some(oracle_framework::ID),
+            PauseTarget::Treasury => Some(treasury_engine::ID),
+            PauseTarget::All => None,
+        }
+    }
 }
 
 #[account]
@@ -463,6 +1083,41 @@ impl VoteRecord {
     pub const LEN: usize = 32 + 1 + 8 + 1;
 }
 
+/// A voter's locked XGT and the boosted voting weight it carries until unlock
+#[account]
+pub struct XgtStake {
+    pub owner: Pubkey,
+    pub xgt_mint: Pubkey,
+    /// XGT held in the stake vault
+    pub amount: u64,
+    /// Lock-boosted voting weight, fixed at stake time
+    pub weight: u64,
+    /// Slot from which the stake can be withdrawn and stops counting for votes
+    pub unlock_slot: u64,
+}
+
+impl XgtStake {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8;
+
+    /// Weight this stake carries in a vote at `slot` (zero once unlocked)
+    pub fn voting_weight(&self, slot: u64) -> u64 {
+        if slot < self.unlock_slot {
+            self.weight
+        } else {
+            0
+        }
+    }
+
+    /// XGT still locked at `slot` (zero once unlocked)
+    pub fn locked_amount(&self, slot: u64) -> u64 {
+        if slot < self.unlock_slot {
+            self.amount
+        } else {
+            0
+        }
+    }
+}
+
 // ========== MEDIUM-SEVERITY FIX (VULN-022): EVENT EMISSION ==========
 #[event]
 pub struct GovernanceInitialized {
@@ -479,6 +1134,8 @@ pub struct ProposalCreated {
     pub nonce: u64,
     pub title: String,
     pub timelock_eta: i64,
+    pub voting_end_slot: u64,
+    pub action: ProposalAction,
     pub timestamp: i64,
 }
 
@@ -510,6 +1167,52 @@ pub struct ProposalExecuted {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct GovernancePauseExecuted {
+    pub proposal_id: Pubkey,
+    pub target: PauseTarget,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GovernanceOracleDeprecated {
+    pub proposal_id: Pubkey,
+    pub oracle: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct XgtStaked {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub lock_slots: u64,
+    pub weight: u64,
+    pub unlock_slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct XgtUnstaked {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QuorumMultiplierUpdated {
+    pub admin: Pubkey,
+    pub action_kind: ProposalActionKind,
+    pub multiplier_bps: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalThresholdUpdated {
+    pub admin: Pubkey,
+    pub proposal_threshold: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct GovernancePaused {
     pub admin: Pubkey,
@@ -521,6 +1224,13 @@ pub struct GovernanceUnpaused {
     pub admin: Pubkey,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct GovernanceVersionMigrated {
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
 // ========== END EVENT DEFINITIONS ==========
 
 #[error_code]
@@ -549,5 +1259,33 @@ pub enum GovernanceError {
     NotPaused,  // VULN-020: Circuit breaker
     #[msg("Unauthorized - caller is not admin")]
     Unauthorized,  // VULN-020: Circuit breaker
+    #[msg("Pause proposal accounts must be [program, state] pairs for each targeted program")]
+    InvalidPauseTargetAccounts,
+    #[msg("Stake amount must be greater than zero")]
+    InvalidStakeAmount,
+    #[msg("Lock duration must be between 1 slot and MAX_STAKE_LOCK_SLOTS")]
+    InvalidLockDuration,
+    #[msg("An XGT stake is already active for this owner")]
+    StakeAlreadyActive,
+    #[msg("No active XGT stake")]
+    NoActiveStake,
+    #[msg("Stake is still locked")]
+    StakeLocked,
+    #[msg("Proposal action accounts do not match the expected program and state")]
+    InvalidProposalAccounts,
+    #[msg("Quorum multiplier must be at least 1x (10000 bps)")]
+    InvalidQuorumMultiplier,
+    #[msg("Token mint is not the canonical XGT governance mint")]
+    InvalidGovernanceToken,
+    #[msg("Creator's locked XGT is below the proposal threshold")]
+    BelowProposalThreshold,
+    #[msg("Account version doesn't match this program; migrate it first")]
+    VersionMismatch,
+    #[msg("Voting period must be non-negative and fit in slots")]
+    InvalidVotingPeriod,
+    #[msg("Voting on this proposal has ended")]
+    VotingClosed,
+    #[msg("Stake unlocks before the proposal's voting ends")]
+    StakeUnlocksBeforeVotingEnd,
 }
 
@@ -2,6 +2,8 @@ use anchor_lang::prelude::*;
 
 declare_id!("Liqd111111111111111111111111111111111111111");
 
+pub const KEEPER_SEED: &[u8] = b"keeper";
+
 #[program]
 pub mod liquidation_engine {
     use super::*;
@@ -145,6 +147,70 @@ pub mod liquidation_engine {
 
         Ok(())
     }
+
+    /// Register the signer as a liquidation keeper; registration counts as its first heartbeat
+    pub fn register_keeper(ctx: Context<RegisterKeeper>) -> Result<()> {
+        let keeper = &mut ctx.accounts.keeper;
+        let clock = Clock::get()?;
+        keeper.authority = ctx.accounts.authority.key();
+        keeper.registered_slot = clock.slot;
+        keeper.last_heartbeat_slot = clock.slot;
+        msg!("🤖 Keeper registered: {}", keeper.authority);
+
+        emit!(KeeperHeartbeat {
+            keeper: keeper.authority,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record that a keeper is alive and monitoring; callable by the keeper at any time
+    pub fn keeper_heartbeat(ctx: Context<KeeperHeartbeatCtx>) -> Result<()> {
+        let keeper = &mut ctx.accounts.keeper;
+        let clock = Clock::get()?;
+        keeper.last_heartbeat_slot = clock.slot;
+        msg!("💓 Keeper heartbeat: {} (slot {})", keeper.authority, clock.slot);
+
+        emit!(KeeperHeartbeat {
+            keeper: keeper.authority,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Report keepers (passed as remaining accounts) whose last heartbeat is older than
+    /// `max_age_slots`. Read-only view for operators monitoring keeper liveness.
+    pub fn list_stale_keepers(ctx: Context<ListStaleKeepers>, max_age_slots: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let mut stale_count: u32 = 0;
+
+        for info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*info.owner, crate::ID, LiquidationError::InvalidKeeperAccount);
+            let data = info.try_borrow_data()?;
+            let keeper = Keeper::try_deserialize(&mut &data[..])?;
+
+            let age_slots = clock.slot.saturating_sub(keeper.last_heartbeat_slot);
+            if age_slots > max_age_slots {
+                stale_count = stale_count.saturating_add(1);
+                msg!("⚠️ Stale keeper {}: last heartbeat {} slots ago", keeper.authority, age_slots);
+                emit!(KeeperStale {
+                    keeper: keeper.authority,
+                    last_heartbeat_slot: keeper.last_heartbeat_slot,
+                    age_slots,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+        }
+
+        msg!("🔎 {} of {} keepers stale (max age {} slots)",
+            stale_count, ctx.remaining_accounts.len(), max_age_slots);
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -192,6 +258,36 @@ pub struct DistributeLiquidationProceeds<'info> {
     pub authority: Account<'info, LiquidationAuthority>,
 }
 
+#[derive(Accounts)]
+pub struct RegisterKeeper<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Keeper::LEN,
+        seeds = [KEEPER_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub keeper: Account<'info, Keeper>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct KeeperHeartbeatCtx<'info> {
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, authority.key().as_ref()],
+        bump,
+        has_one = authority @ LiquidationError::Unauthorized
+    )]
+    pub keeper: Account<'info, Keeper>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ListStaleKeepers {}
+
 #[account]
 pub struct LiquidationAuthority {
     pub owner: Pubkey,
@@ -211,6 +307,17 @@ impl LiquidationAuthority {
     }
 }
 
+#[account]
+pub struct Keeper {
+    pub authority: Pubkey,
+    pub registered_slot: u64,
+    pub last_heartbeat_slot: u64,
+}
+
+impl Keeper {
+    pub const LEN: usize = 32 + 8 + 8;
+}
+
 // ========== MEDIUM-SEVERITY FIX (VULN-022): EVENT EMISSION ==========
 #[event]
 pub struct LiquidationTriggered {
@@ -246,6 +353,21 @@ pub struct ProceedsDistributed {
     pub user_return: u64,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct KeeperHeartbeat {
+    pub keeper: Pubkey,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeeperStale {
+    pub keeper: Pubkey,
+    pub last_heartbeat_slot: u64,
+    pub age_slots: u64,
+    pub timestamp: i64,
+}
 // ========== END EVENT DEFINITIONS ==========
 
 #[error_code]
@@ -264,5 +386,7 @@ pub enum LiquidationError {
     SlippageTooHigh,
     #[msg("Invalid liquidator - cannot be default address")]
     InvalidLiquidator,  // SECURITY FIX (VULN-063)
+    #[msg("Account is not a registered keeper")]
+    InvalidKeeperAccount,
 }
 
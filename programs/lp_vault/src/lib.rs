@@ -1,8 +1,37 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, MintTo, Burn};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, Burn, FreezeAccount, Mint, MintTo, ThawAccount, Token, TokenAccount, Transfer};
+use layout_migration::{load_any_layout, store_migrated};
 
 declare_id!("BKCWUpTk3B1yXoFAWugnmLM5s2S1HWpmNiAE3ZJQn5eE");
 
+/// Default minimum deposit (1 USDC, 6 decimals) to block rounding-dust deposits
+pub const DEFAULT_MIN_DEPOSIT: u64 = 1_000_000;
+
+/// Default minimum first deposit (1,000 USDC) so the base share price isn't set by dust
+pub const DEFAULT_SEED_DEPOSIT: u64 = 1_000_000_000;
+
+/// Fixed-point scale of nav_per_share and the high-water mark (1_000_000 = 1 USDC per share)
+pub const NAV_PRECISION: u64 = 1_000_000;
+
+/// Ceiling on the performance fee, in bps of gains above the high-water mark
+pub const MAX_PERFORMANCE_FEE_BPS: u64 = 2_000;
+
+/// Most deposits processed by one batch_deposit, bounded by compute
+pub const MAX_BATCH_DEPOSITS: usize = 8;
+
+/// Remaining accounts per batch_deposit entry: user_usdc_account, user_lp_token_account, lp_position
+pub const BATCH_DEPOSIT_ACCOUNTS_PER_ENTRY: usize = 3;
+
+/// Seed prefix for per-LP LpPosition PDAs
+pub const LP_POSITION_SEED: &[u8] = b"lp_position";
+
+/// Treasury program whose `[b"treasury"]` PDA funds insurance from protocol revenue
+pub const TREASURY_PROGRAM_ID: Pubkey = pubkey!("Tres111111111111111111111111111111111111111");
+
+/// LPVaultState layout version; bump with any change to its fields
+pub const STATE_VERSION: u8 = 1;
+
 #[program]
 pub mod lp_vault {
     use super::*;
@@ -15,6 +44,17 @@ pub mod lp_vault {
         vault.utilization = 0;
         vault.authority = authority;
         vault.paused = false;  // Start unpaused
+        vault.transfers_paused = false;
+        vault.min_deposit = DEFAULT_MIN_DEPOSIT;
+        vault.high_water_mark = NAV_PRECISION;
+        vault.performance_fee_bps = 0;
+        vault.max_write_off_per_call = 0;
+        vault.max_write_off_per_epoch = 0;
+        vault.write_off_epoch = 0;
+        vault.written_off_this_epoch = 0;
+        vault.insurance_balance = 0;
+        vault.seed_deposit = DEFAULT_SEED_DEPOSIT;
+        vault.version = STATE_VERSION;
 
         // Emit event for monitoring
         let clock = Clock::get()?;
@@ -38,36 +78,63 @@ pub mod lp_vault {
         Ok(())
     }
 
+    /// Rewrite the vault in the current layout stamped with STATE_VERSION (authority only).
+    /// A vault created before the version byte existed is decoded in that layout and grown,
+    /// with explicit defaults for every field added since; until migrated, every mutating
+    /// instruction rejects it with VersionMismatch.
+    pub fn migrate_vault_version(ctx: Context<MigrateVaultVersion>) -> Result<()> {
+        let vault_info = ctx.accounts.vault.to_account_info();
+        let space = 8 + LPVaultState::LEN;
+        let mut vault: LPVaultState =
+            load_any_layout(&vault_info, &crate::ID, space, LPVaultState::from_legacy)?;
+        vault.assert_authority(ctx.accounts.authority.key())?;
+
+        let from_version = vault.version;
+        // An account written by a newer program can't be downgraded
+        require!(from_version <= STATE_VERSION, VaultError::VersionMismatch);
+        vault.version = STATE_VERSION;
+        store_migrated(
+            &vault_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            space,
+            &vault,
+        )?;
+        msg!("✅ Vault migrated from version {} to {}", from_version, STATE_VERSION);
+
+        let clock = Clock::get()?;
+        emit!(VaultVersionMigrated {
+            from_version,
+            to_version: STATE_VERSION,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     pub fn deposit_usdc(ctx: Context<DepositUsdc>, amount: u64) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
 
         // ========== CIRCUIT BREAKER CHECK (VULN-020) ==========
         require!(!vault.paused, VaultError::VaultPaused);
         // ========== END CIRCUIT BREAKER CHECK ==========
+        // New shares would reach unfrozen accounts while LP transfers are restricted
+        require!(!vault.transfers_paused, VaultError::LpTransfersPaused);
 
         require!(amount > 0, VaultError::ZeroAmount);
-        let pre_shares = vault.total_shares;
-        let pre_price = vault.share_price();
+        require!(amount >= vault.min_deposit, VaultError::DepositTooSmall);
 
-        let shares = if vault.total_shares == 0 {
-            // First deposit: 1:1 ratio (amount in lamports = shares)
-            amount
-        } else {
-            // Subsequent deposits: shares = (amount * total_shares) / vault_balance
-            // To avoid overflow, use u128 for intermediate calculation
-            let amount_u128 = amount as u128;
-            let total_shares_u128 = vault.total_shares as u128;
-            let balance_u128 = vault.vault_usdc_balance.max(1) as u128;
-
-            let shares_u128 = (amount_u128 * total_shares_u128) / balance_u128;
+        // Credit yield on the balance held so far before new shares join it
+        accrue_lp_position(
+            &mut ctx.accounts.lp_position,
+            ctx.accounts.user_lp_token_account.amount,
+            vault.nav_per_share()?,
+        )?;
 
-            // Convert back to u64, check for overflow
-            let shares = shares_u128
-                .try_into()
-                .map_err(|_| VaultError::MathOverflow)?;
+        let pre_shares = vault.total_shares;
+        let pre_price = vault.share_price();
 
-            shares
-        };
+        let shares = vault.shares_for_deposit(amount)?;
 
         // STEP 1: Transfer USDC from user to vault
         msg!("Transferring {} USDC from user to vault", amount);
@@ -128,6 +195,112 @@ pub mod lp_vault {
         Ok(())
     }
 
+    /// Deposit on behalf of several LPs in one transaction.
+    ///
+    /// Remaining accounts are (user_usdc_account, user_lp_token_account, lp_position) triples,
+    /// one per entry in `amounts`. The depositor must own or be delegated each USDC account;
+    /// the LP's yield is accrued and shares are minted with the same math as deposit_usdc.
+    pub fn batch_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchDeposit<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.vault.paused, VaultError::VaultPaused);
+        require!(!ctx.accounts.vault.transfers_paused, VaultError::LpTransfersPaused);
+        require!(!amounts.is_empty(), VaultError::ZeroAmount);
+        require!(amounts.len() <= MAX_BATCH_DEPOSITS, VaultError::BatchTooLarge);
+        require!(
+            ctx.remaining_accounts.len() == amounts.len() * BATCH_DEPOSIT_ACCOUNTS_PER_ENTRY,
+            VaultError::BatchAccountMismatch
+        );
+
+        let vault_bump = ctx.bumps.vault;
+        let seeds = &[b"vault".as_ref(), &[vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+        let clock = Clock::get()?;
+
+        for (amount, entry) in amounts
+            .iter()
+            .copied()
+            .zip(ctx.remaining_accounts.chunks(BATCH_DEPOSIT_ACCOUNTS_PER_ENTRY))
+        {
+            let [user_usdc_account, user_lp_token_account, lp_position_info] = entry else {
+                return err!(VaultError::BatchAccountMismatch);
+            };
+
+            let vault = &ctx.accounts.vault;
+            require!(amount > 0, VaultError::ZeroAmount);
+            require!(amount >= vault.min_deposit, VaultError::DepositTooSmall);
+            assert_lp_token_account(user_lp_token_account, ctx.accounts.lp_token_mint.key())?;
+            let (lp_owner, lp_balance) = {
+                let data = user_lp_token_account.try_borrow_data()?;
+                let token_account = TokenAccount::try_deserialize(&mut &data[..])?;
+                (token_account.owner, token_account.amount)
+            };
+
+            // Credit yield on the balance held so far before new shares join it
+            let mut lp_position = Account::<LpPosition>::try_from(lp_position_info)?;
+            let (lp_position_pda, _) =
+                Pubkey::find_program_address(&[LP_POSITION_SEED, lp_owner.as_ref()], &crate::ID);
+            require_keys_eq!(lp_position_pda, *lp_position_info.key, VaultError::BatchAccountMismatch);
+            accrue_lp_position(&mut lp_position, lp_balance, vault.nav_per_share()?)?;
+            lp_position.exit(&crate::ID)?;
+
+            let pre_shares = vault.total_shares;
+            let pre_price = vault.share_price();
+            let shares = vault.shares_for_deposit(amount)?;
+
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: user_usdc_account.clone(),
+                        to: ctx.accounts.vault_usdc_account.to_account_info(),
+                        authority: ctx.accounts.depositor.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.lp_token_mint.to_account_info(),
+                        to: user_lp_token_account.clone(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                shares,
+            )?;
+
+            let vault = &mut ctx.accounts.vault;
+            vault.total_shares = vault.total_shares.saturating_add(shares);
+            vault.vault_usdc_balance = vault.vault_usdc_balance.saturating_add(amount);
+            if pre_shares > 0 {
+                require!(vault.share_price() >= pre_price, VaultError::SharePriceRegression);
+            }
+            vault.update_utilization();
+
+            emit!(LPDeposited {
+                user: lp_owner,
+                amount,
+                shares,
+                total_shares: vault.total_shares,
+                vault_balance: vault.vault_usdc_balance,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        msg!(
+            "📦 Batch deposit of {} entries by {}",
+            amounts.len(),
+            ctx.accounts.depositor.key()
+        );
+
+        Ok(())
+    }
+
     pub fn withdraw_usdc(ctx: Context<WithdrawUsdc>, shares: u64) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
 
@@ -138,6 +311,30 @@ pub mod lp_vault {
         require!(shares > 0, VaultError::ZeroAmount);
         require!(shares <= vault.total_shares, VaultError::InsufficientShares);
 
+        // Credit yield on the full balance before any of it is redeemed
+        accrue_lp_position(
+            &mut ctx.accounts.lp_position,
+            ctx.accounts.user_lp_token_account.amount,
+            vault.nav_per_share()?,
+        )?;
+
+        // ========== SOLVENCY CHECK ==========
+        // If tracked USDC isn't actually held, early withdrawers would drain the shortfall
+        // from everyone else; freeze withdrawals until the gap is made good
+        let actual_balance = ctx.accounts.vault_usdc_account.amount;
+        if !vault.is_solvent(actual_balance) {
+            let tracked_balance = vault.tracked_balance();
+            msg!("🚨 Vault insolvent: tracks {} USDC but holds {}", tracked_balance, actual_balance);
+            emit!(VaultInsolvent {
+                tracked_balance,
+                actual_balance,
+                shortfall: tracked_balance - actual_balance,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+            return err!(VaultError::VaultInsolvent);
+        }
+        // ========== END SOLVENCY CHECK ==========
+
         let amount = vault.redeem_amount(shares)?;
 
         // Check that vault has enough available liquidity (not locked for financing)
@@ -199,6 +396,13 @@ pub mod lp_vault {
         Ok(())
     }
 
+    /// Redeem the caller's entire LP token balance in one call, leaving no dust shares behind
+    pub fn withdraw_all(ctx: Context<WithdrawUsdc>) -> Result<()> {
+        let shares = ctx.accounts.user_lp_token_account.amount;
+        msg!("Withdrawing full LP balance: {} shares", shares);
+        withdraw_usdc(ctx, shares)
+    }
+
     pub fn mint_shares(ctx: Context<ManageShares>, amount: u64) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         vault.assert_authority(ctx.accounts.authority.key())?;
@@ -334,6 +538,11 @@ pub mod lp_vault {
 
         // ========== END SECURITY FIX ==========
 
+        // ========== BAD DEBT SOCIALIZATION CAP ==========
+        // Bound how much loss can be pushed onto LPs per call and per epoch
+        let clock = Clock::get()?;
+        vault.record_write_off(bad_debt, clock.epoch)?;
+
         msg!("Writing off bad debt: {} USDC (financing: {}, shortfall: {})",
              bad_debt, financing_amount, bad_debt);
 
@@ -341,21 +550,26 @@ pub mod lp_vault {
         let unlock_amount = financing_amount.min(vault.locked_for_financing);
         vault.locked_for_financing = vault.locked_for_financing.saturating_sub(unlock_amount);
 
-        // Write off the bad debt by reducing vault balance
+        // The insurance buffer absorbs the loss first
+        let insurance_used = bad_debt.min(vault.insurance_balance);
+        vault.insurance_balance -= insurance_used;
+
+        // Write off the rest by reducing vault balance
         // This automatically distributes the loss to all LPs prorata through share value reduction
-        vault.vault_usdc_balance = vault.vault_usdc_balance.saturating_sub(bad_debt);
+        vault.vault_usdc_balance = vault.vault_usdc_balance.saturating_sub(bad_debt - insurance_used);
 
         vault.update_utilization();
 
-        msg!("Bad debt written off. New vault balance: {}, locked: {}",
-             vault.vault_usdc_balance, vault.locked_for_financing);
+        msg!("Bad debt written off ({} covered by insurance). New vault balance: {}, locked: {}",
+             insurance_used, vault.vault_usdc_balance, vault.locked_for_financing);
 
         // Emit event for monitoring
-        let clock = Clock::get()?;
         emit!(BadDebtWrittenOff {
             authority: ctx.accounts.authority.key(),
             financing_amount,
             bad_debt,
+            insurance_used,
+            insurance_balance: vault.insurance_balance,
             vault_balance: vault.vault_usdc_balance,
             locked_for_financing: vault.locked_for_financing,
             timestamp: clock.unix_timestamp,
@@ -364,6 +578,162 @@ pub mod lp_vault {
         Ok(())
     }
 
+    /// Add USDC to the insurance buffer that absorbs bad debt before LPs do. Only the vault
+    /// authority or the treasury PDA (by CPI from protocol revenue) may fund it.
+    pub fn fund_insurance(ctx: Context<FundInsurance>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.assert_insurance_funder(ctx.accounts.funder.key())?;
+        require!(amount > 0, VaultError::ZeroAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.funder_usdc_account.to_account_info(),
+                    to: ctx.accounts.vault_usdc_account.to_account_info(),
+                    authority: ctx.accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.insurance_balance = vault.insurance_balance
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        msg!("🛡️ Insurance funded with {} USDC (buffer: {})", amount, vault.insurance_balance);
+
+        let clock = Clock::get()?;
+        emit!(InsuranceFunded {
+            funder: ctx.accounts.funder.key(),
+            amount,
+            insurance_balance: vault.insurance_balance,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the minimum accepted deposit (admin only)
+    pub fn set_min_deposit(ctx: Context<AdminVaultAction>, min_deposit: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.assert_authority(ctx.accounts.authority.key())?;
+
+        vault.min_deposit = min_deposit;
+        msg!("✅ Minimum deposit set to {}", min_deposit);
+
+        Ok(())
+    }
+
+    /// Set the minimum first deposit into an empty vault (admin only). 0 = no seed minimum
+    pub fn set_seed_deposit(ctx: Context<AdminVaultAction>, seed_deposit: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.assert_authority(ctx.accounts.authority.key())?;
+
+        vault.seed_deposit = seed_deposit;
+        msg!("✅ Seed deposit set to {}", seed_deposit);
+
+        Ok(())
+    }
+
+    /// Set the performance fee charged on LP gains above the high-water mark (admin only)
+    pub fn set_performance_fee(ctx: Context<AdminVaultAction>, performance_fee_bps: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.assert_authority(ctx.accounts.authority.key())?;
+        require!(
+            performance_fee_bps <= MAX_PERFORMANCE_FEE_BPS,
+            VaultError::InvalidPerformanceFee
+        );
+
+        vault.performance_fee_bps = performance_fee_bps;
+        msg!("✅ Performance fee set to {}bps", performance_fee_bps);
+
+        Ok(())
+    }
+
+    /// Set the bad-debt write-off caps per call and per epoch; 0 disables a cap (admin only)
+    pub fn set_write_off_caps(
+        ctx: Context<AdminVaultAction>,
+        max_write_off_per_call: u64,
+        max_write_off_per_epoch: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.assert_authority(ctx.accounts.authority.key())?;
+
+        vault.max_write_off_per_call = max_write_off_per_call;
+        vault.max_write_off_per_epoch = max_write_off_per_epoch;
+        msg!("✅ Write-off caps set: {} per call, {} per epoch",
+             max_write_off_per_call, max_write_off_per_epoch);
+
+        Ok(())
+    }
+
+    /// Pay the performance fee on gains above the high-water mark to `fee_destination` (admin only)
+    pub fn collect_performance_fee(ctx: Context<CollectPerformanceFee>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.assert_authority(ctx.accounts.authority.key())?;
+
+        let fee = vault.accrue_performance_fee()?;
+        if fee == 0 {
+            msg!("No gains above the high-water mark; no performance fee due");
+            return Ok(());
+        }
+        require!(
+            vault.vault_usdc_balance >= vault.locked_for_financing,
+            VaultError::InsufficientLiquidity
+        );
+
+        let vault_bump = ctx.bumps.vault;
+        let seeds = &[b"vault".as_ref(), &[vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_usdc_account.to_account_info(),
+                    to: ctx.accounts.fee_destination.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fee,
+        )?;
+        vault.update_utilization();
+
+        msg!("Performance fee of {} USDC collected, high-water mark {}", fee, vault.high_water_mark);
+
+        let clock = Clock::get()?;
+        emit!(PerformanceFeeCollected {
+            fee,
+            high_water_mark: vault.high_water_mark,
+            vault_balance: vault.vault_usdc_balance,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open the caller's yield tracker, starting from the current nav_per_share
+    pub fn open_lp_position(ctx: Context<OpenLpPosition>) -> Result<()> {
+        let lp_position = &mut ctx.accounts.lp_position;
+        lp_position.owner = ctx.accounts.owner.key();
+        lp_position.last_nav_per_share = ctx.accounts.vault.nav_per_share()?;
+        lp_position.cumulative_yield_earned = 0;
+        msg!("✅ LP position opened at NAV {}", lp_position.last_nav_per_share);
+        Ok(())
+    }
+
+    /// Accrue yield on the LP's current share balance since their last report and emit
+    /// their lifetime earnings (permissionless). Deposits and withdrawals accrue the same
+    /// way before changing the balance.
+    pub fn report_lp_yield(ctx: Context<ReportLpYield>) -> Result<()> {
+        let nav = ctx.accounts.vault.nav_per_share()?;
+        let shares = ctx.accounts.lp_token_account.amount;
+        accrue_lp_position(&mut ctx.accounts.lp_position, shares, nav)?;
+        Ok(())
+    }
+
     // ========== MEDIUM-SEVERITY FIX (VULN-020): CIRCUIT BREAKER ==========
     /// Pause the vault (admin only)
     pub fn pause_vault(ctx: Context<AdminVaultAction>) -> Result<()> {
@@ -405,11 +775,181 @@ pub mod lp_vault {
         Ok(())
     }
     // ========== END CIRCUIT BREAKER ==========
+
+    // ========== LP TRANSFER RESTRICTIONS ==========
+    /// Freeze the LP token accounts passed as remaining accounts (admin only).
+    ///
+    /// Requires the vault PDA to hold freeze authority over the LP mint.
+    /// Frozen accounts cannot transfer, receive, or burn LP shares until thawed.
+    /// Also sets `transfers_paused`, which blocks deposits: freshly minted shares
+    /// would land in unfrozen accounts and sidestep the restriction.
+    pub fn freeze_lp_transfers<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LpTransferControl<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), VaultError::NoLpTokenAccounts);
+
+        let vault_bump = ctx.bumps.vault;
+        let seeds = &[b"vault".as_ref(), &[vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        for lp_token_account in ctx.remaining_accounts.iter() {
+            assert_lp_token_account(lp_token_account, ctx.accounts.lp_token_mint.key())?;
+            token::freeze_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                FreezeAccount {
+                    account: lp_token_account.clone(),
+                    mint: ctx.accounts.lp_token_mint.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        let vault = &mut ctx.accounts.vault;
+        vault.transfers_paused = true;
+        msg!(
+            "🧊 LP TRANSFERS FROZEN for {} account(s) by admin: {}",
+            ctx.remaining_accounts.len(),
+            ctx.accounts.authority.key()
+        );
+
+        let clock = Clock::get()?;
+        emit!(LpTransfersFrozen {
+            admin: ctx.accounts.authority.key(),
+            accounts: ctx.remaining_accounts.len() as u32,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Thaw the LP token accounts passed as remaining accounts (admin only).
+    ///
+    /// Other accounts may still be frozen, so `transfers_paused` is only cleared
+    /// when the admin passes `lift_restriction` with the last of them.
+    pub fn thaw_lp_transfers<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LpTransferControl<'info>>,
+        lift_restriction: bool,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), VaultError::NoLpTokenAccounts);
+
+        let vault_bump = ctx.bumps.vault;
+        let seeds = &[b"vault".as_ref(), &[vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        for lp_token_account in ctx.remaining_accounts.iter() {
+            assert_lp_token_account(lp_token_account, ctx.accounts.lp_token_mint.key())?;
+            token::thaw_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                ThawAccount {
+                    account: lp_token_account.clone(),
+                    mint: ctx.accounts.lp_token_mint.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        let vault = &mut ctx.accounts.vault;
+        if lift_restriction {
+            vault.transfers_paused = false;
+        }
+        msg!(
+            "🔓 LP TRANSFERS THAWED for {} account(s) by admin: {} (restriction {})",
+            ctx.remaining_accounts.len(),
+            ctx.accounts.authority.key(),
+            if vault.transfers_paused { "still active" } else { "lifted" }
+        );
+
+        let clock = Clock::get()?;
+        emit!(LpTransfersThawed {
+            admin: ctx.accounts.authority.key(),
+            accounts: ctx.remaining_accounts.len() as u32,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+    // ========== END LP TRANSFER RESTRICTIONS ==========
+}
+
+/// Ensure a remaining account is an SPL token account of the LP mint.
+fn assert_lp_token_account(account: &AccountInfo, lp_token_mint: Pubkey) -> Result<()> {
+    require_keys_eq!(*account.owner, token::ID, VaultError::InvalidLpTokenAccount);
+    let data = account.try_borrow_data()?;
+    let token_account = TokenAccount::try_deserialize(&mut &data[..])
+        .map_err(|_| error!(VaultError::InvalidLpTokenAccount))?;
+    require_keys_eq!(token_account.mint, lp_token_mint, VaultError::InvalidLpTokenAccount);
+    Ok(())
+}
+
+/// Accrue an LP's yield on `shares` up to `nav` and report it
+fn accrue_lp_position(lp_position: &mut LpPosition, shares: u64, nav: u64) -> Result<()> {
+    let accrued = lp_position.accrue_yield(shares, nav)?;
+    msg!("📈 LP {} earned {} USDC since last report ({} lifetime)",
+        lp_position.owner, accrued, lp_position.cumulative_yield_earned);
+
+    let clock = Clock::get()?;
+    emit!(LpYieldReported {
+        owner: lp_position.owner,
+        shares,
+        nav_per_share: nav,
+        yield_accrued: accrued,
+        cumulative_yield_earned: lp_position.cumulative_yield_earned,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// LPVaultState as the vault was written before the layout carried a version byte
+#[derive(AnchorDeserialize)]
+struct LegacyLPVaultState {
+    total_shares: u64,
+    vault_usdc_balance: u64,
+    locked_for_financing: u64,
+    utilization: u64,
+    authority: Pubkey,
+    paused: bool,
+}
+
+impl LPVaultState {
+    /// Carry a pre-version vault over with initialize_vault's defaults for every field added
+    /// since, except the high-water mark: that starts at the current NAV so gains made
+    /// before performance fees existed are never charged.
+    fn from_legacy(legacy: LegacyLPVaultState) -> Self {
+        let mut vault = Self {
+            total_shares: legacy.total_shares,
+            vault_usdc_balance: legacy.vault_usdc_balance,
+            locked_for_financing: legacy.locked_for_financing,
+            utilization: legacy.utilization,
+            authority: legacy.authority,
+            paused: legacy.paused,
+            transfers_paused: false,
+            min_deposit: DEFAULT_MIN_DEPOSIT,
+            high_water_mark: NAV_PRECISION,
+            performance_fee_bps: 0,
+            max_write_off_per_call: 0,
+            max_write_off_per_epoch: 0,
+            write_off_epoch: 0,
+            written_off_this_epoch: 0,
+            insurance_balance: 0,
+            seed_deposit: DEFAULT_SEED_DEPOSIT,
+            version: 0,
+        };
+        vault.high_water_mark = vault.nav_per_share().unwrap_or(NAV_PRECISION).max(NAV_PRECISION);
+        vault
+    }
 }
 
 #[derive(Accounts)]
 pub struct DepositUsdc<'info> {
-    #[account(mut, seeds = [b"vault"], bump)]
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
     pub vault: Account<'info, LPVaultState>,
 
     /// LP token mint (vault is mint authority)
@@ -438,13 +978,52 @@ pub struct DepositUsdc<'info> {
     )]
     pub vault_usdc_account: Account<'info, TokenAccount>,
 
+    /// User's yield tracker (opened via open_lp_position), accrued before the balance changes
+    #[account(
+        mut,
+        seeds = [LP_POSITION_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
     pub user: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct BatchDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
+    pub vault: Account<'info, LPVaultState>,
+
+    /// LP token mint (vault is mint authority)
+    #[account(mut)]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    /// Vault's USDC account (destination for every deposit in the batch)
+    #[account(
+        mut,
+        constraint = vault_usdc_account.owner == vault.key()
+    )]
+    pub vault_usdc_account: Account<'info, TokenAccount>,
+
+    /// Owner or delegate of every source USDC account
+    pub depositor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct WithdrawUsdc<'info> {
-    #[account(mut, seeds = [b"vault"], bump)]
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
     pub vault: Account<'info, LPVaultState>,
 
     /// LP token mint (vault burns from user)
@@ -473,20 +1052,38 @@ pub struct WithdrawUsdc<'info> {
     )]
     pub vault_usdc_account: Account<'info, TokenAccount>,
 
+    /// User's yield tracker (opened via open_lp_position), accrued before the balance changes
+    #[account(
+        mut,
+        seeds = [LP_POSITION_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
     pub user: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct ManageShares<'info> {
-    #[account(mut, seeds = [b"vault"], bump)]
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
     pub vault: Account<'info, LPVaultState>,
     pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct AllocateFinancing<'info> {
-    #[account(mut, seeds = [b"vault"], bump)]
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
     pub vault: Account<'info, LPVaultState>,
 
     pub financed_mint: Account<'info, Mint>,
@@ -511,7 +1108,12 @@ pub struct AllocateFinancing<'info> {
 
 #[derive(Accounts)]
 pub struct ReleaseFinancing<'info> {
-    #[account(mut, seeds = [b"vault"], bump)]
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
     pub vault: Account<'info, LPVaultState>,
 
     pub financed_mint: Account<'info, Mint>,
@@ -536,13 +1138,46 @@ pub struct ReleaseFinancing<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct FundInsurance<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
+    pub vault: Account<'info, LPVaultState>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    /// Funder's USDC account (source of the insurance contribution)
+    #[account(
+        mut,
+        constraint = funder_usdc_account.owner == funder.key(),
+        constraint = funder_usdc_account.mint == usdc_mint.key() @ VaultError::InvalidInsuranceAccount
+    )]
+    pub funder_usdc_account: Account<'info, TokenAccount>,
+
+    /// Vault's USDC ATA (holds the insurance buffer alongside LP funds)
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_usdc_account: Account<'info, TokenAccount>,
+
+    pub funder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct WriteOffBadDebt<'info> {
     #[account(
         mut,
         seeds = [b"vault"],
         bump,
-        has_one = authority @ VaultError::Unauthorized
+        has_one = authority @ VaultError::Unauthorized,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
     )]
     pub vault: Account<'info, LPVaultState>,
 
@@ -557,7 +1192,8 @@ pub struct AdminVaultAction<'info> {
         mut,
         seeds = [b"vault"],
         bump,
-        has_one = authority @ VaultError::Unauthorized
+        has_one = authority @ VaultError::Unauthorized,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
     )]
     pub vault: Account<'info, LPVaultState>,
 
@@ -566,6 +1202,61 @@ pub struct AdminVaultAction<'info> {
 }
 // ========== END CIRCUIT BREAKER ACCOUNTS ==========
 
+#[derive(Accounts)]
+pub struct CollectPerformanceFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        has_one = authority @ VaultError::Unauthorized,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
+    pub vault: Account<'info, LPVaultState>,
+
+    /// Vault's USDC account (source of the fee)
+    #[account(
+        mut,
+        constraint = vault_usdc_account.owner == vault.key()
+    )]
+    pub vault_usdc_account: Account<'info, TokenAccount>,
+
+    /// USDC account receiving the performance fee
+    #[account(
+        mut,
+        constraint = fee_destination.mint == vault_usdc_account.mint
+    )]
+    pub fee_destination: Account<'info, TokenAccount>,
+
+    /// Vault authority
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+// ========== LP TRANSFER RESTRICTION ACCOUNTS ==========
+#[derive(Accounts)]
+pub struct LpTransferControl<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        has_one = authority @ VaultError::Unauthorized,
+        constraint = vault.version == STATE_VERSION @ VaultError::VersionMismatch
+    )]
+    pub vault: Account<'info, LPVaultState>,
+
+    /// LP token mint (vault must hold freeze authority)
+    #[account(
+        constraint = lp_token_mint.freeze_authority == COption::Some(vault.key())
+            @ VaultError::MissingFreezeAuthority
+    )]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    /// Vault authority
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+// ========== END LP TRANSFER RESTRICTION ACCOUNTS ==========
+
 #[derive(Accounts)]
 pub struct InitializeVault<'info> {
     #[account(
@@ -576,11 +1267,82 @@ pub struct InitializeVault<'info> {
         space = 8 + LPVaultState::LEN
     )]
     pub vault: Account<'info, LPVaultState>,
+
+    /// LP token mint (deposits mint shares with the vault PDA as signer)
+    #[account(
+        constraint = lp_token_mint.mint_authority == COption::Some(vault.key())
+            @ VaultError::InvalidMintAuthority
+    )]
+    pub lp_token_mint: Account<'info, Mint>,
+
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct OpenLpPosition<'info> {
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: Account<'info, LPVaultState>,
+
+    #[account(
+        init,
+        seeds = [LP_POSITION_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + LpPosition::LEN
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReportLpYield<'info> {
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: Account<'info, LPVaultState>,
+
+    #[account(
+        mut,
+        seeds = [LP_POSITION_SEED, lp_position.owner.as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    /// LP token mint (vault must be mint authority)
+    #[account(
+        constraint = lp_token_mint.mint_authority == COption::Some(vault.key())
+            @ VaultError::InvalidMintAuthority
+    )]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    /// The LP's share balance
+    #[account(
+        constraint = lp_token_account.mint == lp_token_mint.key() @ VaultError::InvalidLpTokenAccount,
+        constraint = lp_token_account.owner == lp_position.owner @ VaultError::InvalidLpTokenAccount
+    )]
+    pub lp_token_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVaultVersion<'info> {
+    /// CHECK: Vault PDA in either layout; owner, discriminator and authority are checked by the handler
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// Vault authority; pays for the added fields
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct MigrateVaultAuthority<'info> {
     #[account(mut, seeds = [b"vault"], bump)]
@@ -596,16 +1358,37 @@ pub struct LPVaultState {
     pub utilization: u64,
     pub authority: Pubkey,
     pub paused: bool,  // CIRCUIT BREAKER (VULN-020)
+    pub transfers_paused: bool,  // LP share transfer restriction
+    pub min_deposit: u64,
+    pub high_water_mark: u64,  // Peak nav_per_share a performance fee was charged up to
+    pub performance_fee_bps: u64,
+    pub max_write_off_per_call: u64,  // 0 = uncapped
+    pub max_write_off_per_epoch: u64,  // 0 = uncapped
+    pub write_off_epoch: u64,
+    pub written_off_this_epoch: u64,
+    pub insurance_balance: u64,  // USDC buffer absorbing bad debt before LPs; not part of LP NAV
+    pub seed_deposit: u64,  // Minimum first deposit into an empty vault; 0 = none
+    pub version: u8,  // Layout version (STATE_VERSION at init); stale vaults must be migrated
 }
 
 impl LPVaultState {
-    pub const LEN: usize = 8 * 4 + 32 + 1; // 4 u64s + 1 Pubkey + 1 bool
+    pub const LEN: usize = 8 * 4 + 32 + 1 + 1 + 8 + 8 + 8 + 8 * 4 + 8 + 8 + 1; // 4 u64s + 1 Pubkey + 2 bools + min_deposit + HWM + fee bps + write-off caps + insurance + seed deposit + version
 
     pub fn assert_authority(&self, authority: Pubkey) -> Result<()> {
         require_keys_eq!(authority, self.authority, VaultError::Unauthorized);
         Ok(())
     }
 
+    /// Insurance may be funded by the vault authority or the treasury PDA
+    pub fn assert_insurance_funder(&self, funder: Pubkey) -> Result<()> {
+        let (treasury, _) = Pubkey::find_program_address(&[b"treasury"], &TREASURY_PROGRAM_ID);
+        require!(
+            funder == self.authority || funder == treasury,
+            VaultError::Unauthorized
+        );
+        Ok(())
+    }
+
     // LP APY model placeholder: APY = utilization * base_rate
     pub fn lp_apy(&self, base_rate_bps: u64) -> u64 {
         self.utilization
@@ -624,6 +1407,99 @@ impl LPVaultState {
         }
     }
 
+    /// Shares minted for a deposit of `amount` at the current vault balance.
+    pub fn shares_for_deposit(&self, amount: u64) -> Result<u64> {
+        let shares = if self.total_shares == 0 {
+            // First deposit: 1:1 ratio (amount in lamports = shares). It fixes the base
+            // share price, so it must be large enough that later rounding stays negligible
+            require!(amount >= self.seed_deposit, VaultError::InsufficientSeedDeposit);
+            amount
+        } else {
+            // Subsequent deposits: shares = (amount * total_shares) / vault_balance
+            // To avoid overflow, use u128 for intermediate calculation
+            let amount_u128 = amount as u128;
+            let total_shares_u128 = self.total_shares as u128;
+            let balance_u128 = self.vault_usdc_balance.max(1) as u128;
+
+            let shares_u128 = (amount_u128 * total_shares_u128) / balance_u128;
+
+            // Convert back to u64, check for overflow
+            shares_u128
+                .try_into()
+                .map_err(|_| VaultError::MathOverflow)?
+        };
+        // Rounding must never turn a deposit into a donation
+        require!(shares > 0, VaultError::ZeroSharesMinted);
+        Ok(shares)
+    }
+
+    /// Vault USDC per share, scaled by NAV_PRECISION
+    pub fn nav_per_share(&self) -> Result<u64> {
+        if self.total_shares == 0 {
+            return Ok(NAV_PRECISION);
+        }
+        let nav: u64 = ((self.vault_usdc_balance as u128) * (NAV_PRECISION as u128)
+            / (self.total_shares as u128))
+            .try_into()
+            .map_err(|_| VaultError::MathOverflow)?;
+        Ok(nav)
+    }
+
+    /// Deduct the performance fee owed on gains above the high-water mark and return it.
+    /// Nothing is charged while nav_per_share is at or below the mark, so LPs recovering
+    /// from a loss pay no fee until they pass the previous peak. The mark then moves to
+    /// the post-fee NAV.
+    pub fn accrue_performance_fee(&mut self) -> Result<u64> {
+        let nav = self.nav_per_share()?;
+        if self.total_shares == 0 || nav <= self.high_water_mark {
+            return Ok(0);
+        }
+
+        let gain = ((nav - self.high_water_mark) as u128) * (self.total_shares as u128)
+            / (NAV_PRECISION as u128);
+        let fee: u64 = (gain * (self.performance_fee_bps as u128) / 10_000)
+            .try_into()
+            .map_err(|_| VaultError::MathOverflow)?;
+
+        self.vault_usdc_balance = self.vault_usdc_balance
+            .checked_sub(fee)
+            .ok_or(VaultError::MathOverflow)?;
+        self.high_water_mark = self.nav_per_share()?;
+        Ok(fee)
+    }
+
+    /// Count `bad_debt` against the write-off caps for `epoch`, rejecting it if either
+    /// cap would be exceeded. The running total resets when the epoch changes.
+    pub fn record_write_off(&mut self, bad_debt: u64, epoch: u64) -> Result<()> {
+        if epoch != self.write_off_epoch {
+            self.write_off_epoch = epoch;
+            self.written_off_this_epoch = 0;
+        }
+        require!(
+            self.max_write_off_per_call == 0 || bad_debt <= self.max_write_off_per_call,
+            VaultError::WriteOffCapExceeded
+        );
+        let written_off = self.written_off_this_epoch
+            .checked_add(bad_debt)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(
+            self.max_write_off_per_epoch == 0 || written_off <= self.max_write_off_per_epoch,
+            VaultError::WriteOffCapExceeded
+        );
+        self.written_off_this_epoch = written_off;
+        Ok(())
+    }
+
+    /// USDC the vault's token account should hold: LP funds plus the insurance buffer
+    pub fn tracked_balance(&self) -> u64 {
+        self.vault_usdc_balance.saturating_add(self.insurance_balance)
+    }
+
+    /// Tracked balance is fully backed by the USDC the vault's token account holds
+    pub fn is_solvent(&self, actual_balance: u64) -> bool {
+        actual_balance >= self.tracked_balance()
+    }
+
     pub fn redeem_amount(&self, shares: u64) -> Result<u64> {
         require!(self.total_shares > 0, VaultError::NoShares);
 
@@ -655,6 +1531,36 @@ impl LPVaultState {
     }
 }
 
+/// Per-LP lifetime yield tracker, separate from the LP's current balance
+#[account]
+pub struct LpPosition {
+    pub owner: Pubkey,
+    pub last_nav_per_share: u64,  // Highest NAV yield has been credited up to
+    pub cumulative_yield_earned: u64,
+}
+
+impl LpPosition {
+    pub const LEN: usize = 32 + 8 + 8; // owner + last NAV + lifetime yield
+
+    /// Credit yield on `shares` for NAV growth above the last credited NAV and return it.
+    /// A drawdown credits nothing and leaves the mark in place, so a recovery is not
+    /// counted as new yield.
+    pub fn accrue_yield(&mut self, shares: u64, nav_per_share: u64) -> Result<u64> {
+        if nav_per_share <= self.last_nav_per_share {
+            return Ok(0);
+        }
+        let accrued: u64 = ((nav_per_share - self.last_nav_per_share) as u128 * shares as u128
+            / NAV_PRECISION as u128)
+            .try_into()
+            .map_err(|_| VaultError::MathOverflow)?;
+        self.cumulative_yield_earned = self.cumulative_yield_earned
+            .checked_add(accrued)
+            .ok_or(VaultError::MathOverflow)?;
+        self.last_nav_per_share = nav_per_share;
+        Ok(accrued)
+    }
+}
+
 // ========== MEDIUM-SEVERITY FIX (VULN-022): EVENT EMISSION ==========
 #[event]
 pub struct VaultInitialized {
@@ -687,11 +1593,21 @@ pub struct BadDebtWrittenOff {
     pub authority: Pubkey,
     pub financing_amount: u64,
     pub bad_debt: u64,
+    pub insurance_used: u64,
+    pub insurance_balance: u64,
     pub vault_balance: u64,
     pub locked_for_financing: u64,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct InsuranceFunded {
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub insurance_balance: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct VaultPaused {
     pub admin: Pubkey,
@@ -704,6 +1620,20 @@ pub struct VaultUnpaused {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct LpTransfersFrozen {
+    pub admin: Pubkey,
+    pub accounts: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpTransfersThawed {
+    pub admin: Pubkey,
+    pub accounts: u32,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct LPDeposited {
     pub user: Pubkey,
@@ -723,6 +1653,39 @@ pub struct LPWithdrawn {
     pub vault_balance: u64,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct VaultInsolvent {
+    pub tracked_balance: u64,
+    pub actual_balance: u64,
+    pub shortfall: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpYieldReported {
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub nav_per_share: u64,
+    pub yield_accrued: u64,
+    pub cumulative_yield_earned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PerformanceFeeCollected {
+    pub fee: u64,
+    pub high_water_mark: u64,
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultVersionMigrated {
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
 // ========== END EVENT DEFINITIONS ==========
 
 #[error_code]
@@ -751,4 +1714,34 @@ pub enum VaultError {
     AlreadyPaused,  // VULN-020: Circuit breaker
     #[msg("Vault is not paused")]
     NotPaused,  // VULN-020: Circuit breaker
+    #[msg("Vault does not hold freeze authority over the LP mint")]
+    MissingFreezeAuthority,
+    #[msg("Account is not a token account of the LP mint")]
+    InvalidLpTokenAccount,
+    #[msg("No LP token accounts supplied")]
+    NoLpTokenAccounts,
+    #[msg("Deposit is below the vault minimum")]
+    DepositTooSmall,
+    #[msg("Deposit would mint zero shares")]
+    ZeroSharesMinted,
+    #[msg("Performance fee exceeds the maximum")]
+    InvalidPerformanceFee,
+    #[msg("Batch exceeds the maximum number of deposits")]
+    BatchTooLarge,
+    #[msg("Remaining accounts do not match the batch amounts")]
+    BatchAccountMismatch,
+    #[msg("LP mint authority is not the vault PDA")]
+    InvalidMintAuthority,
+    #[msg("Vault holds less USDC than it tracks; withdrawals are blocked")]
+    VaultInsolvent,
+    #[msg("Bad debt write-off exceeds the per-call or per-epoch cap")]
+    WriteOffCapExceeded,
+    #[msg("First deposit into an empty vault is below the seed deposit")]
+    InsufficientSeedDeposit,
+    #[msg("Account version doesn't match this program; migrate it first")]
+    VersionMismatch,
+    #[msg("LP transfers are restricted; deposits are closed until the restriction is lifted")]
+    LpTransfersPaused,
+    #[msg("Insurance funding must move the vault's USDC mint into the vault's USDC ATA")]
+    InvalidInsuranceAccount,
 }
@@ -1,8 +1,15 @@
 use anchor_lang::prelude::*;
 use fixed::types::I80F48;
+use layout_migration::{load_any_layout, store_migrated};
 
 declare_id!("Arcf111111111111111111111111111111111111111");
 
+/// Oldest price, in slots since the last update, still fresh enough for liquidations
+pub const MAX_STALENESS_SLOTS: u64 = 100; // ~40 seconds at 400ms/slot
+
+/// OracleState layout version; bump with any change to its fields
+pub const STATE_VERSION: u8 = 1;
+
 #[program]
 pub mod oracle_framework {
     use super::*;
@@ -21,6 +28,10 @@ pub mod oracle_framework {
         oracle.frozen_slot = 0;
         oracle.last_update_slot = 0;
         oracle.paused = false;  // Start unpaused
+        oracle.min_update_interval_slots = 0;  // No spacing until the admin sets one
+        oracle.pyth_update_slot = 0;
+        oracle.switchboard_update_slot = 0;
+        oracle.version = STATE_VERSION;
         msg!("✅ Global oracle initialized with protocol admin: {}", protocol_admin);
 
         // Emit event for monitoring
@@ -52,11 +63,21 @@ pub mod oracle_framework {
         // ========== END SECURITY FIX (VULN-055) ==========
 
         let clock = Clock::get()?;
+        // Spaced-out updates make it costlier to drag the time-weighted average
+        require!(oracle.update_allowed_at(&source, clock.slot), OracleError::UpdateTooFrequent);
         oracle.last_update_slot = clock.slot;
 
         let source_id = match source {
-            OracleSource::Pyth => { oracle.pyth_price = price; 0 },
-            OracleSource::Switchboard => { oracle.switchboard_price = price; 1 },
+            OracleSource::Pyth => {
+                oracle.pyth_price = price;
+                oracle.pyth_update_slot = clock.slot;
+                0
+            },
+            OracleSource::Switchboard => {
+                oracle.switchboard_price = price;
+                oracle.switchboard_update_slot = clock.slot;
+                1
+            },
             OracleSource::SyntheticTwap => { oracle.synthetic_twap = price; 2 },
         };
 
@@ -75,19 +96,13 @@ pub mod oracle_framework {
         let oracle = &ctx.accounts.oracle;
         let clock = Clock::get()?;
 
-        // Check for staleness
+        // Check each feed for staleness: a fresh pyth update must not vouch for a stale switchboard
         require!(
-            clock.slot.saturating_sub(oracle.last_update_slot) <= max_staleness_slots,
+            oracle.feeds_fresh(clock.slot, max_staleness_slots),
             OracleError::StalePrice
         );
 
-        let p = oracle.pyth_price;
-        let s = oracle.switchboard_price;
-        require!(p > 0 && s > 0, OracleError::InvalidPrice);
-
-        let diff = (p - s).abs() as u128;
-        let base = p.max(s) as u128;
-        let bps = diff.checked_mul(10_000).unwrap_or(0).checked_div(base.max(1)).unwrap_or(0) as u16;
+        let bps = oracle.feed_divergence_bps().ok_or(OracleError::InvalidPrice)?;
         require!(bps <= tolerance_bps, OracleError::InconsistentFeeds);
         Ok(())
     }
@@ -108,13 +123,15 @@ pub mod oracle_framework {
 
         let clock = Clock::get()?;
 
+        // Spot input is the median of the live feeds (their midpoint while only two report)
+        let spot_price = median_price(&[oracle.pyth_price, oracle.switchboard_price])
+            .ok_or(OracleError::InvalidPrice)?;
+
         // Time-weighted calculation: weight newer prices based on time elapsed
-        // If this is the first TWAP or window has reset, use current average
+        // If this is the first TWAP or window has reset, use current spot price
         if oracle.last_twap_window == 0 || oracle.last_update_slot == 0 {
-            // Initial TWAP: simple average of available feeds
-            let mut accumulator = I80F48::from_num(oracle.pyth_price);
-            accumulator += I80F48::from_num(oracle.switchboard_price);
-            oracle.synthetic_twap = (accumulator / I80F48::from_num(2)).to_num();
+            // Initial TWAP: median of available feeds
+            oracle.synthetic_twap = spot_price;
             oracle.last_twap_window = window;
             msg!("✅ Initial TWAP calculated: {}", oracle.synthetic_twap);
             return Ok(());
@@ -127,7 +144,7 @@ pub mod oracle_framework {
         // Time-weighted formula: TWAP_new = (TWAP_old * window + price_new * slots_elapsed) / (window + slots_elapsed)
         // This gives more weight to recent prices while preserving historical average
         let old_twap = I80F48::from_num(oracle.synthetic_twap);
-        let current_price = I80F48::from_num((oracle.pyth_price + oracle.switchboard_price) / 2);
+        let current_price = I80F48::from_num(spot_price);
         let window_weight = I80F48::from_num(window);
         let elapsed_weight = I80F48::from_num(slots_elapsed);
 
@@ -170,21 +187,19 @@ pub mod oracle_framework {
 
         // ========== SECURITY FIX (VULN-054): ENFORCE STALENESS CHECK ==========
         // Prevent using stale prices for critical operations like liquidations
-        const MAX_STALENESS_SLOTS: u64 = 100; // ~40 seconds at 400ms/slot
         let clock = Clock::get()?;
-        let slots_since_update = clock.slot.saturating_sub(oracle.last_update_slot);
+        let slots_since_update = oracle.slots_since_update(clock.slot);
 
-        require!(
-            slots_since_update <= MAX_STALENESS_SLOTS,
-            OracleError::StalePrice
-        );
+        require!(!oracle.is_stale(clock.slot), OracleError::StalePrice);
         msg!("✅ Price freshness validated: updated {} slots ago (max {})",
             slots_since_update, MAX_STALENESS_SLOTS);
         // ========== END SECURITY FIX (VULN-054) ==========
 
-        oracle.frozen_price = oracle.synthetic_twap;
+        // Median across feeds: a single compromised source cannot move the liquidation price
+        oracle.frozen_price = oracle.median_price().ok_or(OracleError::InvalidPrice)?;
         oracle.frozen_slot = clock.slot;
-        msg!("✅ Oracle snapshot frozen at price: {}", oracle.frozen_price);
+        msg!("✅ Oracle snapshot frozen at median price: {} (pyth {}, switchboard {}, twap {})",
+            oracle.frozen_price, oracle.pyth_price, oracle.switchboard_price, oracle.synthetic_twap);
 
         // Emit event for monitoring
         emit!(SnapshotFrozen {
@@ -197,6 +212,46 @@ pub mod oracle_framework {
         Ok(())
     }
 
+    /// Emit an OracleFreshness heartbeat for monitoring (permissionless, read-only)
+    pub fn report_oracle_freshness(ctx: Context<ReportOracleFreshness>) -> Result<()> {
+        let oracle = &ctx.accounts.oracle;
+        let clock = Clock::get()?;
+        let slots_since_update = oracle.slots_since_update(clock.slot);
+        let stale = oracle.is_stale(clock.slot);
+
+        msg!("📡 Oracle last updated {} slots ago (stale: {})", slots_since_update, stale);
+        emit!(OracleFreshness {
+            last_update_slot: oracle.last_update_slot,
+            slots_since_update,
+            stale,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the minimum slots between price updates (admin only). 0 = no limit
+    pub fn set_min_update_interval(ctx: Context<AdminOracleAction>, min_update_interval_slots: u64) -> Result<()> {
+        let oracle = &mut ctx.accounts.oracle;
+
+        require!(
+            ctx.accounts.protocol_admin.key() == oracle.protocol_admin,
+            OracleError::Unauthorized
+        );
+
+        oracle.min_update_interval_slots = min_update_interval_slots;
+        msg!("✅ Oracle updates spaced at least {} slots apart", min_update_interval_slots);
+
+        let clock = Clock::get()?;
+        emit!(MinUpdateIntervalUpdated {
+            admin: ctx.accounts.protocol_admin.key(),
+            min_update_interval_slots,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     // ========== MEDIUM-SEVERITY FIX (VULN-020): CIRCUIT BREAKER ==========
     /// Pause oracle price updates (admin only)
     pub fn pause_oracle(ctx: Context<AdminOracleAction>) -> Result<()> {
@@ -248,6 +303,81 @@ pub mod oracle_framework {
         Ok(())
     }
     // ========== END CIRCUIT BREAKER ==========
+
+    /// Rewrite the oracle in the current layout stamped with STATE_VERSION. An oracle
+    /// initialized before the version byte existed is decoded in that layout and grown,
+    /// with explicit defaults for every field added since.
+    pub fn migrate_oracle_version(ctx: Context<MigrateOracleVersion>) -> Result<()> {
+        let oracle_info = ctx.accounts.oracle.to_account_info();
+        let space = 8 + OracleState::LEN;
+        let mut oracle: OracleState =
+            load_any_layout(&oracle_info, &crate::ID, space, OracleState::from_legacy)?;
+
+        require!(
+            ctx.accounts.protocol_admin.key() == oracle.protocol_admin,
+            OracleError::Unauthorized
+        );
+
+        let from_version = oracle.version;
+        // An account written by a newer program can't be downgraded
+        require!(from_version <= STATE_VERSION, OracleError::VersionMismatch);
+        oracle.version = STATE_VERSION;
+        store_migrated(
+            &oracle_info,
+            &ctx.accounts.protocol_admin.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            space,
+            &oracle,
+        )?;
+        msg!("✅ Oracle migrated from version {} to {}", from_version, STATE_VERSION);
+
+        let clock = Clock::get()?;
+        emit!(OracleVersionMigrated {
+            from_version,
+            to_version: STATE_VERSION,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// OracleState as oracles were written before the layout carried a version byte
+#[derive(AnchorDeserialize)]
+struct LegacyOracleState {
+    authority: Pubkey,
+    protocol_admin: Pubkey,
+    pyth_price: i64,
+    switchboard_price: i64,
+    synthetic_twap: i64,
+    last_twap_window: u64,
+    frozen_price: i64,
+    frozen_slot: u64,
+    last_update_slot: u64,
+    paused: bool,
+}
+
+impl OracleState {
+    /// Carry a pre-version oracle over with initialize_oracle's defaults for every field added
+    /// since. No per-feed slot was recorded, so both feeds read as stale until pushed again.
+    fn from_legacy(legacy: LegacyOracleState) -> Self {
+        Self {
+            authority: legacy.authority,
+            protocol_admin: legacy.protocol_admin,
+            pyth_price: legacy.pyth_price,
+            switchboard_price: legacy.switchboard_price,
+            synthetic_twap: legacy.synthetic_twap,
+            last_twap_window: legacy.last_twap_window,
+            frozen_price: legacy.frozen_price,
+            frozen_slot: legacy.frozen_slot,
+            last_update_slot: legacy.last_update_slot,
+            paused: legacy.paused,
+            min_update_interval_slots: 0,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: 0,
+        }
+    }
 }
 
 #[derive(Accounts)]
@@ -270,19 +400,27 @@ pub struct OracleCtx<'info> {
     #[account(
         mut,
         seeds = [b"oracle"],  // SECURITY FIX (VULN-052): Global oracle, not per-user
-        bump
+        bump,
+        constraint = oracle.version == STATE_VERSION @ OracleError::VersionMismatch
     )]
     pub oracle: Account<'info, OracleState>,
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ReportOracleFreshness<'info> {
+    #[account(seeds = [b"oracle"], bump)]
+    pub oracle: Account<'info, OracleState>,
+}
+
 // ========== MEDIUM-SEVERITY FIX (VULN-020): CIRCUIT BREAKER ACCOUNTS ==========
 #[derive(Accounts)]
 pub struct AdminOracleAction<'info> {
     #[account(
         mut,
         seeds = [b"oracle"],
-        bump
+        bump,
+        constraint = oracle.version == STATE_VERSION @ OracleError::VersionMismatch
     )]
     pub oracle: Account<'info, OracleState>,
 
@@ -291,6 +429,23 @@ pub struct AdminOracleAction<'info> {
 }
 // ========== END CIRCUIT BREAKER ACCOUNTS ==========
 
+#[derive(Accounts)]
+pub struct MigrateOracleVersion<'info> {
+    /// CHECK: Oracle PDA in either layout; owner and discriminator are checked by the handler
+    #[account(
+        mut,
+        seeds = [b"oracle"],
+        bump
+    )]
+    pub oracle: UncheckedAccount<'info>,
+
+    /// Protocol admin (must match oracle.protocol_admin); pays for the added fields
+    #[account(mut)]
+    pub protocol_admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct OracleState {
     pub authority: Pubkey,
@@ -303,10 +458,87 @@ pub struct OracleState {
     pub frozen_slot: u64,
     pub last_update_slot: u64,
     pub paused: bool,  // CIRCUIT BREAKER (VULN-020)
+    pub min_update_interval_slots: u64,  // 0 = updates allowed every slot
+    pub pyth_update_slot: u64,
+    pub switchboard_update_slot: u64,
+    pub version: u8,
 }
 
 impl OracleState {
-    pub const LEN: usize = 32 + 32 + 8 * 6 + 8 + 1;  // Updated: 2 Pubkeys + 7 u64s + 1 bool
+    pub const LEN: usize = 32 + 32 + 8 * 6 + 8 + 1 + 8 + 8 * 2 + 1;  // 2 Pubkeys + 7 u64s + 1 bool + min_update_interval_slots + per-feed update slots + version
+
+    /// Median of every populated feed (pyth, switchboard, synthetic TWAP)
+    pub fn median_price(&self) -> Option<i64> {
+        median_price(&[self.pyth_price, self.switchboard_price, self.synthetic_twap])
+    }
+
+    pub fn slots_since_update(&self, current_slot: u64) -> u64 {
+        current_slot.saturating_sub(self.last_update_slot)
+    }
+
+    /// Slot of the last push from `source`; None for the synthetic TWAP, which has no feed slot
+    pub fn source_update_slot(&self, source: &OracleSource) -> Option<u64> {
+        match source {
+            OracleSource::Pyth => Some(self.pyth_update_slot),
+            OracleSource::Switchboard => Some(self.switchboard_update_slot),
+            OracleSource::SyntheticTwap => None,
+        }
+    }
+
+    /// False while `current_slot` is inside min_update_interval_slots of `source`'s last update.
+    /// Each feed is spaced on its own slot, so a pyth push never blocks switchboard.
+    /// A never-updated feed (slot 0) always accepts.
+    pub fn update_allowed_at(&self, source: &OracleSource, current_slot: u64) -> bool {
+        match self.source_update_slot(source) {
+            Some(last_slot) => {
+                self.min_update_interval_slots == 0
+                    || last_slot == 0
+                    || current_slot.saturating_sub(last_slot) >= self.min_update_interval_slots
+            }
+            None => true,
+        }
+    }
+
+    /// True when pyth and switchboard were each updated within `max_staleness_slots`
+    pub fn feeds_fresh(&self, current_slot: u64, max_staleness_slots: u64) -> bool {
+        current_slot.saturating_sub(self.pyth_update_slot) <= max_staleness_slots
+            && current_slot.saturating_sub(self.switchboard_update_slot) <= max_staleness_slots
+    }
+
+    /// True once the last update is older than MAX_STALENESS_SLOTS
+    pub fn is_stale(&self, current_slot: u64) -> bool {
+        self.slots_since_update(current_slot) > MAX_STALENESS_SLOTS
+    }
+
+    /// Pyth/switchboard divergence in bps of the larger price; None until both are positive
+    pub fn feed_divergence_bps(&self) -> Option<u16> {
+        let p = self.pyth_price;
+        let s = self.switchboard_price;
+        if p <= 0 || s <= 0 {
+            return None;
+        }
+        let diff = (p - s).unsigned_abs() as u128;
+        let base = p.max(s) as u128;
+        Some(u16::try_from(diff * 10_000 / base).unwrap_or(u16::MAX))
+    }
+
+}
+
+/// Median of the positive prices; unset (zero) feeds are skipped. With an even count the
+/// two middle prices are averaged. Robust to one bad feed once three or more report.
+pub fn median_price(prices: &[i64]) -> Option<i64> {
+    let mut live: Vec<i64> = prices.iter().copied().filter(|price| *price > 0).collect();
+    if live.is_empty() {
+        return None;
+    }
+    live.sort_unstable();
+
+    let mid = live.len() / 2;
+    if live.len() % 2 == 1 {
+        Some(live[mid])
+    } else {
+        Some(((live[mid - 1] as i128 + live[mid] as i128) / 2) as i64)
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
@@ -360,6 +592,28 @@ pub struct OracleUnpaused {
     pub admin: Pubkey,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct MinUpdateIntervalUpdated {
+    pub admin: Pubkey,
+    pub min_update_interval_slots: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleFreshness {
+    pub last_update_slot: u64,
+    pub slots_since_update: u64,
+    pub stale: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleVersionMigrated {
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
 // ========== END EVENT DEFINITIONS ==========
 
 #[error_code]
@@ -384,5 +638,9 @@ pub enum OracleError {
     AlreadyPaused,  // VULN-020: Circuit breaker
     #[msg("Oracle is not paused")]
     NotPaused,  // VULN-020: Circuit breaker
+    #[msg("Oracle updated too recently; wait for the minimum update interval")]
+    UpdateTooFrequent,
+    #[msg("Account version doesn't match this program; migrate it first")]
+    VersionMismatch,
 }
 
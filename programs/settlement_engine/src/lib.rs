@@ -43,31 +43,46 @@ pub mod settlement_engine {
         settlement.settlement_type = settlement_type;
         settlement.obligations = obligations;
         settlement.collateral_value = collateral_value;
+
+        let clock = Clock::get()?;
+        emit!(SettlementEntered {
+            settlement: settlement.key(),
+            authority: ctx.accounts.authority.key(),
+            settlement_type,
+            obligations,
+            collateral_value,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
     pub fn compute_obligations(ctx: Context<SettlementCtx>, carry_bps: u16) -> Result<()> {
         let settlement = &mut ctx.accounts.settlement;
         let base = settlement.obligations;
-        let carry = (base as u128)
-            .checked_mul(carry_bps as u128)
-            .and_then(|v| v.checked_div(10_000))
-            .ok_or(SettlementError::MathOverflow)? as u64;
+        let carry = mul_div_ceil(base, carry_bps as u64, 10_000)
+            .ok_or(SettlementError::MathOverflow)?;
         settlement.carry = carry;
+
+        let clock = Clock::get()?;
+        emit!(ObligationsComputed {
+            settlement: settlement.key(),
+            settlement_type: settlement.settlement_type,
+            obligations: base,
+            carry_bps,
+            carry,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
     pub fn apply_carry_waterfall(ctx: Context<SettlementCtx>) -> Result<()> {
         let settlement = &mut ctx.accounts.settlement;
         let total = settlement.obligations.saturating_add(settlement.carry);
-        let protocol = (total as u128)
-            .checked_mul(4)
-            .and_then(|v| v.checked_div(100))
-            .ok_or(SettlementError::MathOverflow)? as u64;
-        let lp_treasury = (total as u128)
-            .checked_mul(16)
-            .and_then(|v| v.checked_div(100))
-            .ok_or(SettlementError::MathOverflow)? as u64;
+        // Protocol and LP shares are fees (round up); the user keeps the remainder
+        let protocol = mul_div_ceil(total, 4, 100).ok_or(SettlementError::MathOverflow)?;
+        let lp_treasury = mul_div_ceil(total, 16, 100).ok_or(SettlementError::MathOverflow)?;
         let user = total
             .checked_sub(protocol)
             .and_then(|v| v.checked_sub(lp_treasury))
@@ -75,6 +90,18 @@ pub mod settlement_engine {
         settlement.protocol_share = protocol;
         settlement.lp_treasury_share = lp_treasury;
         settlement.user_share = user;
+
+        let clock = Clock::get()?;
+        emit!(CarryWaterfallApplied {
+            settlement: settlement.key(),
+            settlement_type: settlement.settlement_type,
+            total,
+            protocol_share: protocol,
+            lp_treasury_share: lp_treasury,
+            user_share: user,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
@@ -90,10 +117,30 @@ pub mod settlement_engine {
         } else {
             settlement.carry = 0;
         }
+
+        let clock = Clock::get()?;
+        emit!(ResidualDistributed {
+            settlement: settlement.key(),
+            settlement_type: settlement.settlement_type,
+            repayments,
+            profit_share: settlement.profit_share,
+            carry: settlement.carry,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 }
 
+/// Fees owed to the protocol round up so split remainders stay with it
+fn mul_div_ceil(value: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    let product = (value as u128).checked_mul(numerator as u128)?;
+    u64::try_from(product.div_ceil(denominator as u128)).ok()
+}
+
 // ========== SECURITY FIX (VULN-068): ADD CONFIG ACCOUNT ==========
 #[derive(Accounts)]
 pub struct InitializeSettlementConfig<'info> {
@@ -162,6 +209,49 @@ pub enum SettlementType {
     UsdcRepaymentKeepAsset,
 }
 
+// ========== EVENT EMISSION ==========
+#[event]
+pub struct SettlementEntered {
+    pub settlement: Pubkey,
+    pub authority: Pubkey,
+    pub settlement_type: SettlementType,
+    pub obligations: u64,
+    pub collateral_value: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ObligationsComputed {
+    pub settlement: Pubkey,
+    pub settlement_type: SettlementType,
+    pub obligations: u64,
+    pub carry_bps: u16,
+    pub carry: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CarryWaterfallApplied {
+    pub settlement: Pubkey,
+    pub settlement_type: SettlementType,
+    pub total: u64,
+    pub protocol_share: u64,
+    pub lp_treasury_share: u64,
+    pub user_share: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResidualDistributed {
+    pub settlement: Pubkey,
+    pub settlement_type: SettlementType,
+    pub repayments: u64,
+    pub profit_share: u64,
+    pub carry: u64,
+    pub timestamp: i64,
+}
+// ========== END EVENT EMISSION ==========
+
 #[error_code]
 pub enum SettlementError {
     #[msg("Math overflow")]
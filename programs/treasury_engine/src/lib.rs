@@ -1,7 +1,16 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
+use layout_migration::{load_any_layout, store_migrated};
+use lp_vault::program::LpVault;
 
 declare_id!("Tres111111111111111111111111111111111111111");
 
+/// Default share of accrued yield compounded into XRS (30%)
+pub const DEFAULT_COMPOUND_RATE_BPS: u64 = 3_000;
+
+/// Treasury layout version; bump with any change to its fields
+pub const STATE_VERSION: u8 = 1;
+
 #[program]
 pub mod treasury_engine {
     use super::*;
@@ -17,6 +26,14 @@ pub mod treasury_engine {
         treasury.carry_accrued = 0;
         treasury.compounded_xrs = 0;
         treasury.paused = false;  // Start unpaused
+        treasury.min_compound_interval_slots = 0;
+        treasury.last_compound_slot = 0;
+        treasury.compound_rate_bps = DEFAULT_COMPOUND_RATE_BPS;
+        treasury.compound_destination = CompoundDestination::Xrs;
+        treasury.reserve_balance = 0;
+        treasury.protocol_admin = Pubkey::default();  // Dual authorization off until configured
+        treasury.dual_auth_threshold = 0;
+        treasury.version = STATE_VERSION;
         msg!("✅ Treasury initialized with admin: {}", admin);
         Ok(())
     }
@@ -81,6 +98,48 @@ pub mod treasury_engine {
 
         treasury.co_financing_outstanding =
             treasury.co_financing_outstanding.saturating_add(co_finance_amount);
+
+        let clock = Clock::get()?;
+        emit!(TreasuryAllocated {
+            admin: ctx.accounts.authority.key(),
+            co_finance_amount,
+            co_financing_outstanding: treasury.co_financing_outstanding,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record co-financed capital returning to the treasury when the position closes (admin only)
+    pub fn treasury_recall(ctx: Context<TreasuryCtx>, amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+
+        // ========== CIRCUIT BREAKER CHECK (VULN-020) ==========
+        require!(!treasury.paused, TreasuryError::TreasuryPaused);
+        // ========== END CIRCUIT BREAKER CHECK ==========
+
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            treasury.admin,
+            TreasuryError::Unauthorized
+        );
+
+        treasury.co_financing_outstanding = treasury
+            .co_financing_outstanding
+            .checked_sub(amount)
+            .ok_or(TreasuryError::RecallExceedsOutstanding)?;
+
+        msg!("✅ Co-financing recalled: {} (outstanding: {})",
+             amount, treasury.co_financing_outstanding);
+
+        let clock = Clock::get()?;
+        emit!(TreasuryRecalled {
+            admin: ctx.accounts.authority.key(),
+            amount,
+            co_financing_outstanding: treasury.co_financing_outstanding,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
@@ -106,6 +165,17 @@ pub mod treasury_engine {
 
         treasury.base_fee_accrued = treasury.base_fee_accrued.saturating_add(base_fee);
         treasury.carry_accrued = treasury.carry_accrued.saturating_add(carry);
+
+        let clock = Clock::get()?;
+        emit!(YieldCollected {
+            admin: ctx.accounts.authority.key(),
+            base_fee,
+            carry,
+            base_fee_accrued: treasury.base_fee_accrued,
+            carry_accrued: treasury.carry_accrued,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
@@ -129,13 +199,33 @@ pub mod treasury_engine {
 
         // ========== END SECURITY FIX ==========
 
+        // ========== COMPOUNDING SCHEDULE ==========
+        let clock = Clock::get()?;
+        let next_compound_slot = treasury
+            .last_compound_slot
+            .saturating_add(treasury.min_compound_interval_slots);
+        require!(
+            treasury.last_compound_slot == 0 || clock.slot >= next_compound_slot,
+            TreasuryError::CompoundTooSoon
+        );
+        treasury.last_compound_slot = clock.slot;
+        // ========== END COMPOUNDING SCHEDULE ==========
+
         let yield_total = treasury.base_fee_accrued.saturating_add(treasury.carry_accrued);
         let compound = (yield_total as u128)
-            .checked_mul(30)
-            .and_then(|v| v.checked_div(100))
+            .checked_mul(treasury.compound_rate_bps as u128)
+            .and_then(|v| v.checked_div(10_000))
             .ok_or(TreasuryError::MathOverflow)? as u64;
 
-        treasury.compounded_xrs = treasury.compounded_xrs.saturating_add(compound);
+        let destination = treasury.compound_destination;
+        match destination {
+            CompoundDestination::Xrs => {
+                treasury.compounded_xrs = treasury.compounded_xrs.saturating_add(compound);
+            }
+            CompoundDestination::Reserve => {
+                treasury.reserve_balance = treasury.reserve_balance.saturating_add(compound);
+            }
+        }
 
         // ========== SECURITY FIX (VULN-074): FIX INFINITE COMPOUNDING ==========
 
@@ -143,10 +233,225 @@ pub mod treasury_engine {
         treasury.base_fee_accrued = 0;
         treasury.carry_accrued = 0;
 
-        msg!("✅ Compounded {} XRS, yield reset to prevent double-compounding", compound);
+        msg!("✅ Compounded {} into {:?}, yield reset to prevent double-compounding", compound, destination);
 
         // ========== END SECURITY FIX ==========
 
+        emit!(XrsCompounded {
+            admin: ctx.accounts.authority.key(),
+            yield_total,
+            compounded: compound,
+            destination,
+            compounded_xrs: treasury.compounded_xrs,
+            reserve_balance: treasury.reserve_balance,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Configure how often and how much accrued yield is compounded (admin only)
+    pub fn set_compound_schedule(
+        ctx: Context<AdminTreasuryAction>,
+        min_compound_interval_slots: u64,
+        compound_rate_bps: u64,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+
+        // Validate admin authority
+        require!(
+            ctx.accounts.admin_authority.key() == treasury.admin,
+            TreasuryError::Unauthorized
+        );
+
+        require!(
+            compound_rate_bps <= 10_000,
+            TreasuryError::InvalidCompoundRate
+        );
+
+        treasury.min_compound_interval_slots = min_compound_interval_slots;
+        treasury.compound_rate_bps = compound_rate_bps;
+        msg!("✅ Compound schedule updated: every {} slots, {} bps of yield",
+             min_compound_interval_slots, compound_rate_bps);
+
+        Ok(())
+    }
+
+    /// Choose where compounded yield is credited: XRS or the retained reserve (admin only)
+    pub fn set_compound_destination(
+        ctx: Context<AdminTreasuryAction>,
+        destination: CompoundDestination,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+
+        // Validate admin authority
+        require!(
+            ctx.accounts.admin_authority.key() == treasury.admin,
+            TreasuryError::Unauthorized
+        );
+
+        treasury.compound_destination = destination;
+        msg!("✅ Compounded yield now credited to {:?}", destination);
+
+        Ok(())
+    }
+
+    // ========== DUAL AUTHORIZATION ==========
+    /// Require the protocol admin to co-sign reserve withdrawals of at least `dual_auth_threshold`.
+    /// `protocol_admin` = Pubkey::default() turns the requirement off. Once enabled, changing it
+    /// needs the current protocol admin's signature as well, so neither key can drop the check alone.
+    pub fn set_dual_authorization(
+        ctx: Context<DualAuthTreasuryAction>,
+        protocol_admin: Pubkey,
+        dual_auth_threshold: u64,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+
+        require_keys_eq!(
+            ctx.accounts.admin_authority.key(),
+            treasury.admin,
+            TreasuryError::Unauthorized
+        );
+        require_co_signer(treasury, ctx.accounts.co_signer.as_ref())?;
+        require!(protocol_admin != treasury.admin, TreasuryError::AdminsNotDistinct);
+
+        treasury.protocol_admin = protocol_admin;
+        treasury.dual_auth_threshold = dual_auth_threshold;
+        msg!("✅ Dual authorization: co-signer {} for withdrawals ≥ {}",
+             protocol_admin, dual_auth_threshold);
+
+        let clock = Clock::get()?;
+        emit!(DualAuthorizationUpdated {
+            protocol_admin,
+            dual_auth_threshold,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw reserve USDC from the treasury's USDC account (admin only). High-value
+    /// withdrawals also need the protocol admin's signature when dual authorization is enabled.
+    pub fn treasury_withdraw_reserve(ctx: Context<WithdrawReserve>, amount: u64) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+
+        // ========== CIRCUIT BREAKER CHECK (VULN-020) ==========
+        require!(!treasury.paused, TreasuryError::TreasuryPaused);
+        // ========== END CIRCUIT BREAKER CHECK ==========
+
+        require_keys_eq!(
+            ctx.accounts.admin_authority.key(),
+            treasury.admin,
+            TreasuryError::Unauthorized
+        );
+        if treasury.requires_dual_auth(amount) {
+            require_co_signer(treasury, ctx.accounts.co_signer.as_ref())?;
+            msg!("✅ Dual authorization satisfied for {} withdrawal", amount);
+        }
+        require!(
+            amount <= treasury.reserve_balance
+                && amount <= ctx.accounts.treasury_usdc_account.amount,
+            TreasuryError::InsufficientReserve
+        );
+
+        let treasury_bump = ctx.bumps.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.treasury_usdc_account.to_account_info(),
+                    mint: ctx.accounts.usdc_mint.to_account_info(),
+                    to: ctx.accounts.destination_usdc_account.to_account_info(),
+                    authority: ctx.accounts.treasury.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.usdc_mint.decimals,
+        )?;
+
+        let destination = ctx.accounts.destination_usdc_account.key();
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.reserve_balance -= amount;
+
+        msg!("✅ Withdrew {} from reserve to {} (reserve: {})",
+             amount, destination, treasury.reserve_balance);
+
+        let clock = Clock::get()?;
+        emit!(ReserveWithdrawn {
+            admin: ctx.accounts.admin_authority.key(),
+            co_signer: ctx.accounts.co_signer.as_ref().map(|signer| signer.key()),
+            amount,
+            destination,
+            reserve_balance: treasury.reserve_balance,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+    // ========== END DUAL AUTHORIZATION ==========
+
+    /// Move reserve USDC into the LP vault's insurance buffer via CPI (admin only).
+    /// Bounded by both the reserve balance and the USDC the treasury actually holds.
+    pub fn fund_insurance_from_treasury(ctx: Context<FundInsuranceFromTreasury>, amount: u64) -> Result<()> {
+        // ========== CIRCUIT BREAKER CHECK (VULN-020) ==========
+        require!(!ctx.accounts.treasury.paused, TreasuryError::TreasuryPaused);
+        // ========== END CIRCUIT BREAKER CHECK ==========
+
+        require_keys_eq!(
+            ctx.accounts.admin_authority.key(),
+            ctx.accounts.treasury.admin,
+            TreasuryError::Unauthorized
+        );
+        if ctx.accounts.treasury.requires_dual_auth(amount) {
+            require_co_signer(&ctx.accounts.treasury, ctx.accounts.co_signer.as_ref())?;
+            msg!("✅ Dual authorization satisfied for {} insurance funding", amount);
+        }
+        require!(
+            amount <= ctx.accounts.treasury.reserve_balance
+                && amount <= ctx.accounts.treasury_usdc_account.amount,
+            TreasuryError::InsufficientReserve
+        );
+
+        let treasury_bump = ctx.bumps.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        lp_vault::cpi::fund_insurance(
+            CpiContext::new_with_signer(
+                ctx.accounts.lp_vault_program.to_account_info(),
+                lp_vault::cpi::accounts::FundInsurance {
+                    vault: ctx.accounts.lp_vault.to_account_info(),
+                    usdc_mint: ctx.accounts.usdc_mint.to_account_info(),
+                    funder_usdc_account: ctx.accounts.treasury_usdc_account.to_account_info(),
+                    vault_usdc_account: ctx.accounts.vault_usdc_account.to_account_info(),
+                    funder: ctx.accounts.treasury.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.reserve_balance -= amount;
+
+        msg!("🛡️ Funded LP vault insurance with {} from reserve (reserve: {})",
+             amount, treasury.reserve_balance);
+
+        let clock = Clock::get()?;
+        emit!(InsuranceFundedFromTreasury {
+            admin: ctx.accounts.admin_authority.key(),
+            co_signer: ctx.accounts.co_signer.as_ref().map(|signer| signer.key()),
+            amount,
+            reserve_balance: treasury.reserve_balance,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
@@ -166,6 +471,12 @@ pub mod treasury_engine {
         treasury.paused = true;
         msg!("🛑 TREASURY PAUSED by admin: {}", ctx.accounts.admin_authority.key());
 
+        let clock = Clock::get()?;
+        emit!(TreasuryPaused {
+            admin: ctx.accounts.admin_authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
@@ -184,9 +495,102 @@ pub mod treasury_engine {
         treasury.paused = false;
         msg!("✅ TREASURY UNPAUSED by admin: {}", ctx.accounts.admin_authority.key());
 
+        let clock = Clock::get()?;
+        emit!(TreasuryUnpaused {
+            admin: ctx.accounts.admin_authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
     // ========== END CIRCUIT BREAKER ==========
+
+    /// Rewrite the treasury in the current layout stamped with STATE_VERSION. A treasury
+    /// created before the version byte existed is decoded in that layout and grown, with
+    /// explicit defaults for every field added since.
+    pub fn migrate_treasury_version(ctx: Context<MigrateTreasuryVersion>) -> Result<()> {
+        let treasury_info = ctx.accounts.treasury.to_account_info();
+        let space = 8 + Treasury::LEN;
+        let mut treasury: Treasury =
+            load_any_layout(&treasury_info, &crate::ID, space, Treasury::from_legacy)?;
+
+        require!(
+            ctx.accounts.admin_authority.key() == treasury.admin,
+            TreasuryError::Unauthorized
+        );
+
+        let from_version = treasury.version;
+        // An account written by a newer program can't be downgraded
+        require!(from_version <= STATE_VERSION, TreasuryError::VersionMismatch);
+        treasury.version = STATE_VERSION;
+        store_migrated(
+            &treasury_info,
+            &ctx.accounts.admin_authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            space,
+            &treasury,
+        )?;
+        msg!("✅ Treasury migrated from version {} to {}", from_version, STATE_VERSION);
+
+        let clock = Clock::get()?;
+        emit!(TreasuryVersionMigrated {
+            from_version,
+            to_version: STATE_VERSION,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// Treasury as it was written before the layout carried a version byte
+#[derive(AnchorDeserialize)]
+struct LegacyTreasury {
+    admin: Pubkey,
+    lp_contributed: u64,
+    co_financing_outstanding: u64,
+    base_fee_accrued: u64,
+    carry_accrued: u64,
+    compounded_xrs: u64,
+    paused: bool,
+}
+
+impl Treasury {
+    /// Carry a pre-version treasury over with initialize_treasury's defaults for every field
+    /// added since
+    fn from_legacy(legacy: LegacyTreasury) -> Self {
+        Self {
+            admin: legacy.admin,
+            lp_contributed: legacy.lp_contributed,
+            co_financing_outstanding: legacy.co_financing_outstanding,
+            base_fee_accrued: legacy.base_fee_accrued,
+            carry_accrued: legacy.carry_accrued,
+            compounded_xrs: legacy.compounded_xrs,
+            paused: legacy.paused,
+            min_compound_interval_slots: 0,
+            last_compound_slot: 0,
+            compound_rate_bps: DEFAULT_COMPOUND_RATE_BPS,
+            compound_destination: CompoundDestination::Xrs,
+            reserve_balance: 0,
+            protocol_admin: Pubkey::default(),  // Dual authorization off until configured
+            dual_auth_threshold: 0,
+            version: 0,
+        }
+    }
+}
+
+/// When dual authorization is enabled, the protocol admin must be among the signers
+fn require_co_signer(treasury: &Treasury, co_signer: Option<&Signer>) -> Result<()> {
+    if treasury.protocol_admin == Pubkey::default() {
+        return Ok(());
+    }
+    let co_signer = co_signer.ok_or(TreasuryError::DualAuthorizationRequired)?;
+    require_keys_eq!(
+        co_signer.key(),
+        treasury.protocol_admin,
+        TreasuryError::DualAuthorizationRequired
+    );
+    Ok(())
 }
 
 #[derive(Accounts)]
@@ -211,7 +615,8 @@ pub struct TreasuryCtx<'info> {
     #[account(
         mut,
         seeds = [b"treasury"],
-        bump
+        bump,
+        constraint = treasury.version == STATE_VERSION @ TreasuryError::VersionMismatch
     )]
     pub treasury: Account<'info, Treasury>,
 
@@ -219,13 +624,107 @@ pub struct TreasuryCtx<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct DualAuthTreasuryAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        constraint = treasury.version == STATE_VERSION @ TreasuryError::VersionMismatch
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Admin authority (must match treasury.admin)
+    pub admin_authority: Signer<'info>,
+
+    /// Protocol admin co-signature (required when dual authorization applies)
+    pub co_signer: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        constraint = treasury.version == STATE_VERSION @ TreasuryError::VersionMismatch
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Admin authority (must match treasury.admin)
+    pub admin_authority: Signer<'info>,
+
+    /// Protocol admin co-signature (required when dual authorization applies)
+    pub co_signer: Option<Signer<'info>>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    /// Treasury's USDC account (source), owned by the treasury PDA
+    #[account(
+        mut,
+        constraint = treasury_usdc_account.owner == treasury.key(),
+        constraint = treasury_usdc_account.mint == usdc_mint.key()
+    )]
+    pub treasury_usdc_account: Account<'info, TokenAccount>,
+
+    /// USDC account receiving the withdrawal; must be a different USDC account
+    #[account(
+        mut,
+        constraint = destination_usdc_account.mint == usdc_mint.key() @ TreasuryError::InvalidReserveDestination,
+        constraint = destination_usdc_account.key() != treasury_usdc_account.key() @ TreasuryError::InvalidReserveDestination
+    )]
+    pub destination_usdc_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FundInsuranceFromTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        constraint = treasury.version == STATE_VERSION @ TreasuryError::VersionMismatch
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Admin authority (must match treasury.admin)
+    pub admin_authority: Signer<'info>,
+
+    /// Protocol admin co-signature (required when dual authorization applies)
+    pub co_signer: Option<Signer<'info>>,
+
+    /// USDC mint the insurance buffer is held in
+    pub usdc_mint: Account<'info, Mint>,
+
+    /// Treasury's USDC account (source), owned by the treasury PDA
+    #[account(
+        mut,
+        constraint = treasury_usdc_account.owner == treasury.key(),
+        constraint = treasury_usdc_account.mint == usdc_mint.key()
+    )]
+    pub treasury_usdc_account: Account<'info, TokenAccount>,
+
+    /// CHECK: LP vault state PDA, validated by the lp_vault program
+    #[account(mut)]
+    pub lp_vault: UncheckedAccount<'info>,
+
+    /// CHECK: LP vault's USDC account, validated by the lp_vault program
+    #[account(mut)]
+    pub vault_usdc_account: UncheckedAccount<'info>,
+
+    pub lp_vault_program: Program<'info, LpVault>,
+    pub token_program: Program<'info, Token>,
+}
+
 // ========== MEDIUM-SEVERITY FIX (VULN-020): CIRCUIT BREAKER ACCOUNTS ==========
 #[derive(Accounts)]
 pub struct AdminTreasuryAction<'info> {
     #[account(
         mut,
         seeds = [b"treasury"],
-        bump
+        bump,
+        constraint = treasury.version == STATE_VERSION @ TreasuryError::VersionMismatch
     )]
     pub treasury: Account<'info, Treasury>,
 
@@ -234,6 +733,23 @@ pub struct AdminTreasuryAction<'info> {
 }
 // ========== END CIRCUIT BREAKER ACCOUNTS ==========
 
+#[derive(Accounts)]
+pub struct MigrateTreasuryVersion<'info> {
+    /// CHECK: Treasury PDA in either layout; owner and discriminator are checked by the handler
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Admin authority (must match treasury.admin); pays for the added fields
+    #[account(mut)]
+    pub admin_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct Treasury {
     pub admin: Pubkey,
@@ -243,11 +759,116 @@ pub struct Treasury {
     pub carry_accrued: u64,
     pub compounded_xrs: u64,
     pub paused: bool,  // CIRCUIT BREAKER (VULN-020)
+    pub min_compound_interval_slots: u64,
+    pub last_compound_slot: u64,
+    pub compound_rate_bps: u64,
+    pub compound_destination: CompoundDestination,
+    pub reserve_balance: u64,  // Compounded yield retained rather than reinvested in XRS
+    pub protocol_admin: Pubkey,  // Co-signer for high-value withdrawals (default = dual auth off)
+    pub dual_auth_threshold: u64,  // Withdrawals at or above this need both admins
+    pub version: u8,
 }
 
 impl Treasury {
-    pub const LEN: usize = 32 + 8 * 5 + 1;  // admin + 5 u64s + 1 bool
+    pub const LEN: usize = 32 + 8 * 5 + 1 + 8 * 3 + 1 + 8 + 32 + 8 + 1;  // admin + 5 u64s + 1 bool + compound schedule + destination + reserve + dual auth + version
+
+    /// Whether withdrawing `amount` needs the protocol admin's co-signature
+    pub fn requires_dual_auth(&self, amount: u64) -> bool {
+        self.protocol_admin != Pubkey::default() && amount >= self.dual_auth_threshold
+    }
+}
+
+/// Where treasury_compound_xrs credits the compounded share of yield
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompoundDestination {
+    Xrs,
+    Reserve,
+}
+
+// ========== EVENT EMISSION ==========
+#[event]
+pub struct TreasuryAllocated {
+    pub admin: Pubkey,
+    pub co_finance_amount: u64,
+    pub co_financing_outstanding: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryRecalled {
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub co_financing_outstanding: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct YieldCollected {
+    pub admin: Pubkey,
+    pub base_fee: u64,
+    pub carry: u64,
+    pub base_fee_accrued: u64,
+    pub carry_accrued: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct XrsCompounded {
+    pub admin: Pubkey,
+    pub yield_total: u64,
+    pub compounded: u64,
+    pub destination: CompoundDestination,
+    pub compounded_xrs: u64,
+    pub reserve_balance: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DualAuthorizationUpdated {
+    pub protocol_admin: Pubkey,
+    pub dual_auth_threshold: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReserveWithdrawn {
+    pub admin: Pubkey,
+    pub co_signer: Option<Pubkey>,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub reserve_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFundedFromTreasury {
+    pub admin: Pubkey,
+    pub co_signer: Option<Pubkey>,
+    pub amount: u64,
+    pub reserve_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryPaused {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryUnpaused {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryVersionMigrated {
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
 }
+// ========== END EVENT EMISSION ==========
 
 #[error_code]
 pub enum TreasuryError {
@@ -265,5 +886,21 @@ pub enum TreasuryError {
     AlreadyPaused,  // VULN-020: Circuit breaker
     #[msg("Treasury is not paused")]
     NotPaused,  // VULN-020: Circuit breaker
+    #[msg("Compounding again before the minimum interval has elapsed")]
+    CompoundTooSoon,
+    #[msg("Compound rate exceeds 100%")]
+    InvalidCompoundRate,
+    #[msg("Recall exceeds outstanding co-financing")]
+    RecallExceedsOutstanding,
+    #[msg("Withdrawal requires the protocol admin's co-signature")]
+    DualAuthorizationRequired,
+    #[msg("Protocol admin must differ from the treasury admin")]
+    AdminsNotDistinct,
+    #[msg("Withdrawal exceeds the reserve balance")]
+    InsufficientReserve,
+    #[msg("Account version doesn't match this program; migrate it first")]
+    VersionMismatch,
+    #[msg("Reserve withdrawals must go to a separate account of the treasury's USDC mint")]
+    InvalidReserveDestination,
 }
 
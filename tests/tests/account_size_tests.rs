@@ -0,0 +1,141 @@
+mod common;
+
+use anchor_lang::prelude::{AccountSerialize, Pubkey};
+use common::setup::sample_financing_state;
+use financing_engine::{FinancingState, MAX_ORACLE_SOURCES};
+use governance::{Proposal, ProposalAction};
+use lp_vault::LPVaultState;
+use oracle_framework::OracleState;
+use treasury_engine::{CompoundDestination, Treasury};
+
+/// Serialized account body length, excluding the 8-byte Anchor discriminator
+/// that `space = 8 + LEN` reserves separately.
+fn serialized_len<T: AccountSerialize>(account: &T) -> usize {
+    let mut buf = Vec::new();
+    account.try_serialize(&mut buf).expect("serialize account");
+    buf.len() - 8
+}
+
+#[test]
+fn test_financing_state_fits_len() {
+    let state = FinancingState {
+        financed_mint: Pubkey::new_unique(),
+        financed_amount: u64::MAX,
+        oracle_sources: vec![Pubkey::new_unique(); MAX_ORACLE_SOURCES],
+        delegated_settlement_authority: Pubkey::new_unique(),
+        delegated_liquidation_authority: Pubkey::new_unique(),
+        carry_enabled: true,
+        is_being_liquidated: true,
+        last_collateral_price: u64::MAX,
+        last_price_update_slot: u64::MAX,
+        authorized_receiver: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        frozen: true,
+        frozen_until: i64::MAX,
+        flagger: Pubkey::new_unique(),
+        total_fees_paid: u64::MAX,
+        processing: true,
+        protocol_breach_slot: u64::MAX,
+        realized_swap_price: u64::MAX,
+        swap_slippage_bps: u64::MAX,
+        max_settlement_slippage_bps: u64::MAX,
+        version: u8::MAX,
+        ..sample_financing_state(
+            Pubkey::new_unique(),
+            u64::MAX,
+            Pubkey::new_unique(),
+            u64::MAX,
+            u64::MAX,
+        )
+    };
+
+    assert!(serialized_len(&state) <= FinancingState::LEN);
+}
+
+#[test]
+fn test_proposal_fits_len() {
+    let proposal = Proposal {
+        creator: Pubkey::new_unique(),
+        nonce: u64::MAX,
+        title: "t".repeat(Proposal::MAX_TITLE_LEN),
+        description: "d".repeat(Proposal::MAX_DESCRIPTION_LEN),
+        for_votes: u64::MAX,
+        against_votes: u64::MAX,
+        timelock_eta: i64::MAX,
+        voting_end_slot: u64::MAX,
+        executed: true,
+        action: ProposalAction::DeprecateOracle { oracle: Pubkey::new_unique() },
+    };
+
+    assert!(serialized_len(&proposal) <= Proposal::LEN);
+}
+
+#[test]
+fn test_oracle_state_fits_len() {
+    let oracle = OracleState {
+        authority: Pubkey::new_unique(),
+        protocol_admin: Pubkey::new_unique(),
+        pyth_price: i64::MAX,
+        switchboard_price: i64::MAX,
+        synthetic_twap: i64::MAX,
+        last_twap_window: u64::MAX,
+        frozen_price: i64::MAX,
+        frozen_slot: u64::MAX,
+        last_update_slot: u64::MAX,
+        paused: true,
+        min_update_interval_slots: u64::MAX,
+        pyth_update_slot: u64::MAX,
+        switchboard_update_slot: u64::MAX,
+        version: u8::MAX,
+    };
+
+    assert!(serialized_len(&oracle) <= OracleState::LEN);
+}
+
+#[test]
+fn test_lp_vault_state_fits_len() {
+    let vault = LPVaultState {
+        total_shares: u64::MAX,
+        vault_usdc_balance: u64::MAX,
+        locked_for_financing: u64::MAX,
+        utilization: u64::MAX,
+        authority: Pubkey::new_unique(),
+        paused: true,
+        transfers_paused: true,
+        min_deposit: u64::MAX,
+        high_water_mark: u64::MAX,
+        performance_fee_bps: u64::MAX,
+        max_write_off_per_call: u64::MAX,
+        max_write_off_per_epoch: u64::MAX,
+        write_off_epoch: u64::MAX,
+        written_off_this_epoch: u64::MAX,
+        insurance_balance: u64::MAX,
+        seed_deposit: u64::MAX,
+        version: u8::MAX,
+    };
+
+    assert!(serialized_len(&vault) <= LPVaultState::LEN);
+}
+
+#[test]
+fn test_treasury_fits_len() {
+    let treasury = Treasury {
+        admin: Pubkey::new_unique(),
+        lp_contributed: u64::MAX,
+        co_financing_outstanding: u64::MAX,
+        base_fee_accrued: u64::MAX,
+        carry_accrued: u64::MAX,
+        compounded_xrs: u64::MAX,
+        paused: true,
+        min_compound_interval_slots: u64::MAX,
+        last_compound_slot: u64::MAX,
+        compound_rate_bps: u64::MAX,
+        compound_destination: CompoundDestination::Reserve,
+        reserve_balance: u64::MAX,
+        protocol_admin: Pubkey::new_unique(),
+        dual_auth_threshold: u64::MAX,
+        version: u8::MAX,
+    };
+
+    assert!(serialized_len(&treasury) <= Treasury::LEN);
+}
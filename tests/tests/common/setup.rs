@@ -1,15 +1,28 @@
 use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorSerialize, Discriminator};
 use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::{
+    TransferFee, TransferFeeAmount, TransferFeeConfig,
+};
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+};
+use financing_engine::{
+    AssetExposure, FinancingState, PositionStatus, ProtocolConfig, ProtocolStats,
+    UserPositionCounter, MAX_SETTLEMENT_SLIPPAGE_BPS, STATE_VERSION,
+};
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program_option::COption;
 use solana_program_pack::Pack;
 use solana_program_test::ProgramTest;
 use solana_sdk::account::Account;
-use solana_sdk::signature::Keypair;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{keypair_from_seed, Keypair};
 
-pub const MIN_COLLATERAL_USD: u64 = 100_000_000; // $100 (8 decimals)
+pub const MIN_COLLATERAL_USD: u64 = 10_000_000_000; // $100 (8-decimal USD value)
 pub const MIN_FINANCING_AMOUNT: u64 = 50_000_000; // $50 (6 decimals)
 
 fn financing_engine_processor<'a, 'b, 'c, 'd>(
@@ -174,10 +187,14 @@ pub fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
 }
 
 pub fn mint_data(mint_authority: Pubkey) -> Vec<u8> {
+    mint_data_with_decimals(mint_authority, 6)
+}
+
+pub fn mint_data_with_decimals(mint_authority: Pubkey, decimals: u8) -> Vec<u8> {
     let mint = spl_token::state::Mint {
         mint_authority: COption::Some(mint_authority),
         supply: 0,
-        decimals: 6,
+        decimals,
         is_initialized: true,
         freeze_authority: COption::None,
     };
@@ -186,6 +203,66 @@ pub fn mint_data(mint_authority: Pubkey) -> Vec<u8> {
     data
 }
 
+/// Token-2022 mint with a transfer-fee extension charging `transfer_fee_bps`, capped at `maximum_fee`.
+pub fn fee_bearing_mint_data(mint_authority: Pubkey, transfer_fee_bps: u16, maximum_fee: u64) -> Vec<u8> {
+    let len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::TransferFeeConfig,
+    ])
+    .expect("mint length");
+    let mut data = vec![0u8; len];
+    let mut mint =
+        StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(&mut data)
+            .expect("unpack mint");
+    let fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: maximum_fee.into(),
+        transfer_fee_basis_points: transfer_fee_bps.into(),
+    };
+    let config = mint
+        .init_extension::<TransferFeeConfig>(true)
+        .expect("init transfer fee config");
+    config.older_transfer_fee = fee;
+    config.newer_transfer_fee = fee;
+    mint.base = spl_token_2022::state::Mint {
+        mint_authority: COption::Some(mint_authority),
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    mint.pack_base();
+    mint.init_account_type().expect("init mint account type");
+    data
+}
+
+/// Token-2022 account carrying the transfer-fee amount extension required by fee-bearing mints.
+pub fn token_2022_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+    let len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&[
+        ExtensionType::TransferFeeAmount,
+    ])
+    .expect("account length");
+    let mut data = vec![0u8; len];
+    let mut account =
+        StateWithExtensionsMut::<spl_token_2022::state::Account>::unpack_uninitialized(&mut data)
+            .expect("unpack token account");
+    account
+        .init_extension::<TransferFeeAmount>(true)
+        .expect("init transfer fee amount");
+    account.base = spl_token_2022::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token_2022::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    account.pack_base();
+    account.init_account_type().expect("init token account type");
+    data
+}
+
 pub fn add_mint_account(program_test: &mut ProgramTest, mint: Pubkey, mint_authority: Pubkey) {
     program_test.add_account(
         mint,
@@ -218,6 +295,142 @@ pub fn add_token_account(
     );
 }
 
+/// Add a program account in its pre-version layout: `T`'s discriminator followed by
+/// `legacy`, a struct from `baseline`, allocated at that layout's `legacy_len`. Funded for
+/// the larger of that and the current `space`, so a migration resizes it in place without
+/// a rent top-up.
+pub fn add_baseline_layout_account<T: Discriminator, L: AnchorSerialize>(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    legacy: &L,
+    legacy_len: usize,
+    space: usize,
+    owner: Pubkey,
+) {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).expect("serialize baseline layout");
+    assert!(data.len() <= legacy_len, "baseline layout overflows its allocation");
+    data.resize(legacy_len, 0);
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(legacy_len.max(space)),
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+/// Account layouts as the programs wrote them before state carried a `version` byte,
+/// kept independent of the programs' own legacy decoders
+pub mod baseline {
+    use anchor_lang::prelude::*;
+
+    #[derive(AnchorSerialize)]
+    pub enum PositionStatus {
+        Active,
+        Matured,
+        Liquidated,
+        Closed,
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct FinancingState {
+        pub user_pubkey: Pubkey,
+        pub position_index: u64,
+        pub collateral_mint: Pubkey,
+        pub collateral_amount: u64,
+        pub collateral_usd_value: u64,
+        pub financed_mint: Pubkey,
+        pub financed_amount: u64,
+        pub financed_purchase_price_usdc: u64,
+        pub financed_usd_value: u64,
+        pub deferred_payment_amount: u64,
+        pub markup_fees: u64,
+        pub initial_ltv: u64,
+        pub max_ltv: u64,
+        pub liquidation_threshold: u64,
+        pub term_start: i64,
+        pub term_end: i64,
+        pub carry_enabled: bool,
+        pub oracle_sources: Vec<Pubkey>,
+        pub delegated_settlement_authority: Pubkey,
+        pub delegated_liquidation_authority: Pubkey,
+        pub position_status: PositionStatus,
+        pub is_being_liquidated: bool,
+        pub last_collateral_price: u64,
+        pub last_price_update_slot: u64,
+    }
+
+    impl FinancingState {
+        pub const LEN: usize = 32 + 8 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1
+            + (4 + 32 * 10) + 32 + 32 + 1 + 1 + 8 + 8;
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct LPVaultState {
+        pub total_shares: u64,
+        pub vault_usdc_balance: u64,
+        pub locked_for_financing: u64,
+        pub utilization: u64,
+        pub authority: Pubkey,
+        pub paused: bool,
+    }
+
+    impl LPVaultState {
+        pub const LEN: usize = 8 * 4 + 32 + 1;
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct OracleState {
+        pub authority: Pubkey,
+        pub protocol_admin: Pubkey,
+        pub pyth_price: i64,
+        pub switchboard_price: i64,
+        pub synthetic_twap: i64,
+        pub last_twap_window: u64,
+        pub frozen_price: i64,
+        pub frozen_slot: u64,
+        pub last_update_slot: u64,
+        pub paused: bool,
+    }
+
+    impl OracleState {
+        pub const LEN: usize = 32 + 32 + 8 * 6 + 8 + 1;
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct Treasury {
+        pub admin: Pubkey,
+        pub lp_contributed: u64,
+        pub co_financing_outstanding: u64,
+        pub base_fee_accrued: u64,
+        pub carry_accrued: u64,
+        pub compounded_xrs: u64,
+        pub paused: bool,
+    }
+
+    impl Treasury {
+        pub const LEN: usize = 32 + 8 * 5 + 1;
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct GovernanceConfig {
+        pub quorum_votes: u64,
+        pub voting_period: i64,
+        pub timelock_delay: i64,
+        pub proposal_count: u64,
+        pub admin_authority: Pubkey,
+        pub paused: bool,
+    }
+
+    impl GovernanceConfig {
+        pub const LEN: usize = 8 + 8 + 8 + 8 + 32 + 1;
+    }
+}
+
 pub fn add_mint_and_ata(
     program_test: &mut ProgramTest,
     mint_authority: Pubkey,
@@ -236,7 +449,7 @@ pub fn deterministic_pubkey(seed: u8) -> Pubkey {
 }
 
 pub fn deterministic_keypair(seed: u8) -> Keypair {
-    Keypair::from_seed(&[seed; 32]).expect("deterministic keypair")
+    keypair_from_seed(&[seed; 32]).expect("deterministic keypair")
 }
 
 pub fn oracle_sources() -> Vec<Pubkey> {
@@ -259,11 +472,113 @@ pub fn sample_user_keypair() -> Keypair {
     deterministic_keypair(2)
 }
 
+pub fn sample_protocol_config(admin_authority: Pubkey, protocol_paused: bool) -> ProtocolConfig {
+    ProtocolConfig {
+        admin_authority,
+        protocol_paused,
+        new_financing_paused: false,
+        fee_treasury: admin_authority,
+        liquidation_fee_bps: financing_engine::DEFAULT_PROTOCOL_LIQ_FEE_BPS,
+        max_total_debt: 0,
+        liquidation_penalty_lp_bps: 0,
+        dust_collateral_threshold: 0,
+        max_price_change_bps: financing_engine::DEFAULT_MAX_PRICE_CHANGE_BPS,
+        flag_bounty_usdc: 0,
+        liquidator_bonus_max_bps: financing_engine::EXTERNAL_LIQUIDATOR_BONUS_BPS,
+        liquidator_bonus_min_bps: financing_engine::EXTERNAL_LIQUIDATOR_BONUS_BPS,
+        admin_action_count: 0,
+        last_admin_action_slot: 0,
+        close_factor_bps: financing_engine::DEFAULT_CLOSE_FACTOR_BPS,
+        fee_routing: financing_engine::FeeRouting::default(),
+        deprecated_oracles: Vec::new(),
+        max_implied_apr_bps: 0,
+        force_liq_grace_slots: 0,
+        max_liquidations_per_slot: 0,
+        min_origination_interval_slots: 0,
+        max_liquidation_value_usdc: 0,
+    }
+}
+
+pub fn sample_position_counter(user: Pubkey, open_positions: u8) -> UserPositionCounter {
+    UserPositionCounter {
+        user,
+        open_positions,
+        total_positions: open_positions as u64,
+        last_origination_slot: 0,
+    }
+}
+
+pub fn sample_protocol_stats(total_outstanding_debt: u64) -> ProtocolStats {
+    ProtocolStats {
+        total_outstanding_debt,
+        last_liquidation_slot: 0,
+        liquidations_this_slot: 0,
+    }
+}
+
+pub fn sample_asset_exposure(
+    financed_mint: Pubkey,
+    max_financing: u64,
+    outstanding_financing: u64,
+) -> AssetExposure {
+    AssetExposure {
+        financed_mint,
+        max_financing,
+        outstanding_financing,
+    }
+}
+
+/// Active Murabaha position with a $1 collateral valuation and no markup;
+/// override fields with struct update syntax where a test needs more.
+pub fn sample_financing_state(
+    user_pubkey: Pubkey,
+    position_index: u64,
+    collateral_mint: Pubkey,
+    collateral_amount: u64,
+    deferred_payment_amount: u64,
+) -> FinancingState {
+    FinancingState {
+        user_pubkey,
+        position_index,
+        collateral_mint,
+        collateral_amount,
+        collateral_usd_value: 100_000_000,
+        financed_mint: Pubkey::default(),
+        financed_amount: 0,
+        financed_purchase_price_usdc: deferred_payment_amount,
+        financed_usd_value: deferred_payment_amount,
+        deferred_payment_amount,
+        markup_fees: 0,
+        initial_ltv: 5_000,
+        max_ltv: 8_000,
+        liquidation_threshold: 9_000,
+        term_start: 0,
+        term_end: 0,
+        carry_enabled: false,
+        oracle_sources: vec![],
+        delegated_settlement_authority: Pubkey::default(),
+        delegated_liquidation_authority: Pubkey::default(),
+        position_status: PositionStatus::Active,
+        is_being_liquidated: false,
+        last_collateral_price: 0,
+        last_price_update_slot: 0,
+        authorized_receiver: Pubkey::default(),
+        owner: user_pubkey,
+        frozen: false,
+        frozen_until: 0,
+        flagger: Pubkey::default(),
+        total_fees_paid: 0,
+        processing: false,
+        protocol_breach_slot: 0,
+        realized_swap_price: 0,
+        swap_slippage_bps: 0,
+        max_settlement_slippage_bps: MAX_SETTLEMENT_SLIPPAGE_BPS,
+        version: STATE_VERSION,
+    }
+}
+
 pub fn financing_state_pda(user: Pubkey, position_index: u64) -> (Pubkey, u8) {
-    Pubkey::find_program_address(
-        &[b"financing", user.as_ref(), &position_index.to_le_bytes()],
-        &financing_engine::id(),
-    )
+    financing_engine::derive_position_pda(&user, position_index)
 }
 
 pub fn financing_position_counter_pda(user: Pubkey) -> (Pubkey, u8) {
@@ -282,6 +597,36 @@ pub fn lp_vault_pda() -> (Pubkey, u8) {
     Pubkey::find_program_address(&[b"vault"], &lp_vault::id())
 }
 
+pub fn lp_position_pda(owner: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[lp_vault::LP_POSITION_SEED, owner.as_ref()], &lp_vault::id())
+}
+
+/// Open `owner`'s LP yield tracker, marked at `last_nav_per_share`
+pub fn add_lp_position(program_test: &mut ProgramTest, owner: Pubkey, last_nav_per_share: u64) -> Pubkey {
+    let (lp_position_pda, _) = lp_position_pda(owner);
+    let mut data = Vec::new();
+    anchor_lang::AccountSerialize::try_serialize(
+        &lp_vault::LpPosition {
+            owner,
+            last_nav_per_share,
+            cumulative_yield_earned: 0,
+        },
+        &mut data,
+    )
+    .expect("serialize lp position");
+    program_test.add_account(
+        lp_position_pda,
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    lp_position_pda
+}
+
 pub fn oracle_framework_oracle_pda() -> (Pubkey, u8) {
     Pubkey::find_program_address(&[b"oracle"], &oracle_framework::id())
 }
@@ -290,6 +635,10 @@ pub fn governance_config_pda() -> (Pubkey, u8) {
     Pubkey::find_program_address(&[b"governance_config"], &governance::id())
 }
 
+pub fn governance_authority_pda() -> Pubkey {
+    Pubkey::find_program_address(&[governance::GOVERNANCE_AUTHORITY_SEED], &governance::id()).0
+}
+
 pub fn governance_proposal_pda(creator: Pubkey, nonce: u64) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[b"proposal", creator.as_ref(), &nonce.to_le_bytes()],
@@ -304,6 +653,27 @@ pub fn governance_vote_pda(proposal: Pubkey, voter: Pubkey) -> (Pubkey, u8) {
     )
 }
 
+pub fn governance_stake_pda(owner: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[governance::XGT_STAKE_SEED, owner.as_ref()], &governance::id())
+}
+
+/// XGT stake locked until `unlock_slot`, carrying the boosted weight for `lock_slots`
+pub fn sample_xgt_stake(
+    owner: Pubkey,
+    xgt_mint: Pubkey,
+    amount: u64,
+    lock_slots: u64,
+    unlock_slot: u64,
+) -> governance::XgtStake {
+    governance::XgtStake {
+        owner,
+        xgt_mint,
+        amount,
+        weight: governance::boosted_weight(amount, lock_slots).expect("boosted weight"),
+        unlock_slot,
+    }
+}
+
 pub fn liquidation_state_pda(owner: Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[b"liquidation", owner.as_ref()], &liquidation_engine::id())
 }
@@ -3,20 +3,31 @@ mod common;
 use anchor_lang::prelude::{AccountDeserialize, AccountSerialize, Pubkey};
 use anchor_lang::InstructionData;
 use anchor_lang::ToAccountMetas;
+use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::spl_token;
-use common::setup::{mint_data, token_account_data};
-use governance::{GovernanceConfig, GovernanceError, Proposal, VoteRecord};
+use common::setup::{
+    add_baseline_layout_account, add_financing_engine_program, baseline, governance_authority_pda, governance_stake_pda, mint_data,
+    sample_protocol_config, sample_xgt_stake, token_account_data,
+};
+use financing_engine::ProtocolConfig;
+use governance::{
+    boosted_weight, GovernanceConfig, GovernanceError, PauseTarget, Proposal, ProposalAction,
+    ProposalActionKind, VoteRecord, XgtStake, MAX_STAKE_LOCK_SLOTS, QUORUM_MULTIPLIER_BASE_BPS,
+};
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program_test::{BanksClientError, ProgramTest};
 use solana_sdk::account::Account;
-use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
 use solana_sdk::signature::{Keypair, Signer};
 use solana_sdk::system_instruction;
 use solana_sdk::system_program;
 use solana_sdk::transaction::Transaction;
 use solana_sdk::transaction::TransactionError;
 
+/// Canonical XGT mint recorded in every test governance config
+const XGT_MINT: Pubkey = Pubkey::new_from_array([0x58; 32]);
+
 fn serialize_anchor_account<T: AccountSerialize>(data: &T) -> Vec<u8> {
     let mut buf = Vec::new();
     data.try_serialize(&mut buf).expect("serialize account");
@@ -48,6 +59,10 @@ fn add_governance_config(
         proposal_count: 0,
         admin_authority: admin,
         paused,
+        quorum_multiplier_bps: [QUORUM_MULTIPLIER_BASE_BPS; ProposalActionKind::COUNT],
+        xgt_mint: XGT_MINT,
+        proposal_threshold: 0,
+        version: governance::STATE_VERSION,
     };
     program_test.add_account(
         config_pda,
@@ -75,6 +90,21 @@ fn add_proposal(program_test: &mut ProgramTest, proposal_pda: Pubkey, proposal:
     );
 }
 
+fn add_xgt_stake(program_test: &mut ProgramTest, stake: XgtStake) -> Pubkey {
+    let (stake_pda, _) = governance_stake_pda(stake.owner);
+    program_test.add_account(
+        stake_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&stake),
+            owner: governance::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    stake_pda
+}
+
 #[tokio::test]
 async fn test_create_proposal_with_nonce() {
     let mut program_test = ProgramTest::new(
@@ -110,6 +140,7 @@ async fn test_create_proposal_with_nonce() {
         proposal: proposal_pda,
         governance_config: config_pda,
         creator: creator.pubkey(),
+        creator_stake: None,
         system_program: system_program::id(),
     };
     let ix = Instruction {
@@ -120,6 +151,7 @@ async fn test_create_proposal_with_nonce() {
             title: "Proposal".to_string(),
             description: "Description".to_string(),
             eta: 0,
+            action: ProposalAction::Signal,
         }
         .data(),
     };
@@ -160,6 +192,7 @@ async fn test_create_proposal_with_nonce() {
             title: "Duplicate".to_string(),
             description: "Duplicate".to_string(),
             eta: 0,
+            action: ProposalAction::Signal,
         }
         .data(),
     };
@@ -177,14 +210,15 @@ async fn test_create_proposal_with_nonce() {
     match err {
         BanksClientError::TransactionError(TransactionError::InstructionError(
             _,
-            InstructionError::AccountAlreadyInitialized | InstructionError::AccountAlreadyInUse,
+            // Custom(0) is the system program's AccountAlreadyInUse
+            InstructionError::AccountAlreadyInitialized | InstructionError::Custom(0),
         )) => {}
         other => panic!("unexpected error: {other:?}"),
     }
 }
 
 #[tokio::test]
-async fn test_vote_with_token_balance() {
+async fn test_vote_with_staked_weight() {
     let mut program_test = ProgramTest::new(
         "governance",
         governance::id(),
@@ -212,13 +246,13 @@ async fn test_vote_with_token_balance() {
             for_votes: 0,
             against_votes: 0,
             timelock_eta: 0,
+            voting_end_slot: 1_000,
             executed: false,
+            action: ProposalAction::Signal,
         },
     );
 
-    let xgt_mint = Pubkey::new_unique();
-    let voter_token_account = Pubkey::new_unique();
-    let voter_balance = 2_000u64;
+    let xgt_mint = XGT_MINT;
     program_test.add_account(
         xgt_mint,
         Account {
@@ -229,27 +263,18 @@ async fn test_vote_with_token_balance() {
             rent_epoch: 0,
         },
     );
-    program_test.add_account(
-        voter_token_account,
-        Account {
-            lamports: 1_000_000,
-            data: token_account_data(xgt_mint, voter.pubkey(), voter_balance),
-            owner: spl_token::id(),
-            executable: false,
-            rent_epoch: 0,
-        },
+    // 2_000 XGT locked for half the maximum: 1.5x boost
+    let staked = 2_000u64;
+    let expected_weight = 3_000u64;
+    let voter_stake = add_xgt_stake(
+        &mut program_test,
+        sample_xgt_stake(voter.pubkey(), xgt_mint, staked, MAX_STAKE_LOCK_SLOTS / 2, u64::MAX),
     );
+    // A lapsed lock no longer carries weight
     let zero_voter = Keypair::new();
-    let zero_voter_ata = Pubkey::new_unique();
-    program_test.add_account(
-        zero_voter_ata,
-        Account {
-            lamports: 1_000_000,
-            data: token_account_data(xgt_mint, zero_voter.pubkey(), 0),
-            owner: spl_token::id(),
-            executable: false,
-            rent_epoch: 0,
-        },
+    let zero_voter_stake = add_xgt_stake(
+        &mut program_test,
+        sample_xgt_stake(zero_voter.pubkey(), xgt_mint, staked, MAX_STAKE_LOCK_SLOTS, 0),
     );
 
     let mut context = program_test.start_with_context().await;
@@ -274,7 +299,7 @@ async fn test_vote_with_token_balance() {
         proposal: proposal_pda,
         vote_record: vote_record_pda,
         voter: voter.pubkey(),
-        user_xgt_account: voter_token_account,
+        stake: voter_stake,
         xgt_mint,
         system_program: system_program::id(),
         governance_config: config_pda,
@@ -300,7 +325,7 @@ async fn test_vote_with_token_balance() {
         .expect("proposal exists");
     let mut proposal_data = proposal_account.data.as_slice();
     let proposal = Proposal::try_deserialize(&mut proposal_data).expect("deserialize proposal");
-    assert_eq!(proposal.for_votes, voter_balance);
+    assert_eq!(proposal.for_votes, expected_weight);
 
     let vote_record_account = context
         .banks_client
@@ -311,7 +336,7 @@ async fn test_vote_with_token_balance() {
     let mut vote_record_data = vote_record_account.data.as_slice();
     let vote_record = VoteRecord::try_deserialize(&mut vote_record_data).expect("deserialize vote record");
     assert!(vote_record.has_voted);
-    assert_eq!(vote_record.weight, voter_balance);
+    assert_eq!(vote_record.weight, expected_weight);
 
     let fund_zero_voter = system_instruction::transfer(
         &context.payer.pubkey(),
@@ -334,7 +359,7 @@ async fn test_vote_with_token_balance() {
         proposal: proposal_pda,
         vote_record: zero_vote_record_pda,
         voter: zero_voter.pubkey(),
-        user_xgt_account: zero_voter_ata,
+        stake: zero_voter_stake,
         xgt_mint,
         system_program: system_program::id(),
         governance_config: config_pda,
@@ -354,7 +379,7 @@ async fn test_vote_with_token_balance() {
         .banks_client
         .process_transaction(zero_tx)
         .await
-        .expect_err("zero balance vote should fail");
+        .expect_err("expired stake vote should fail");
     let expected = u32::from(GovernanceError::NoVotingPower);
     match err {
         BanksClientError::TransactionError(TransactionError::InstructionError(
@@ -367,6 +392,316 @@ async fn test_vote_with_token_balance() {
     }
 }
 
+#[tokio::test]
+async fn test_vote_rejects_fake_governance_mint() {
+    let mut program_test = ProgramTest::new(
+        "governance",
+        governance::id(),
+        solana_program_test::processor!(governance_processor),
+    );
+
+    let admin = Keypair::new();
+    let attacker = Keypair::new();
+    let config_pda = add_governance_config(&mut program_test, admin.pubkey(), 1_000, 86_400, 172_800, false);
+
+    let nonce = 1u64;
+    let creator = Keypair::new();
+    let (proposal_pda, _) = Pubkey::find_program_address(
+        &[b"proposal", creator.pubkey().as_ref(), &nonce.to_le_bytes()],
+        &governance::id(),
+    );
+    add_proposal(
+        &mut program_test,
+        proposal_pda,
+        Proposal {
+            creator: creator.pubkey(),
+            nonce,
+            title: "Proposal".to_string(),
+            description: "Description".to_string(),
+            for_votes: 0,
+            against_votes: 0,
+            timelock_eta: 0,
+            voting_end_slot: 1_000,
+            executed: false,
+            action: ProposalAction::Signal,
+        },
+    );
+
+    // A mint the attacker controls, with a huge stake of it
+    let fake_mint = Pubkey::new_unique();
+    program_test.add_account(
+        fake_mint,
+        Account {
+            lamports: 1_000_000,
+            data: mint_data(attacker.pubkey()),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let attacker_stake = add_xgt_stake(
+        &mut program_test,
+        sample_xgt_stake(attacker.pubkey(), fake_mint, u64::MAX / 4, MAX_STAKE_LOCK_SLOTS, u64::MAX),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let fund_attacker = system_instruction::transfer(
+        &context.payer.pubkey(),
+        &attacker.pubkey(),
+        1_000_000_000,
+    );
+    let fund_tx = Transaction::new_signed_with_payer(
+        &[fund_attacker],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(fund_tx).await.unwrap();
+
+    let (vote_record_pda, _) = Pubkey::find_program_address(
+        &[b"vote", proposal_pda.as_ref(), attacker.pubkey().as_ref()],
+        &governance::id(),
+    );
+    let accounts = governance::accounts::Vote {
+        proposal: proposal_pda,
+        vote_record: vote_record_pda,
+        voter: attacker.pubkey(),
+        stake: attacker_stake,
+        xgt_mint: fake_mint,
+        system_program: system_program::id(),
+        governance_config: config_pda,
+    };
+    let ix = Instruction {
+        program_id: governance::id(),
+        accounts: accounts.to_account_metas(None),
+        data: governance::instruction::Vote { support: true }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&attacker.pubkey()),
+        &[&attacker],
+        context.last_blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("vote with a non-canonical mint should fail");
+    let expected = u32::from(GovernanceError::InvalidGovernanceToken);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => {
+            assert_eq!(code, expected);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_vote_rejects_stake_unlocking_before_voting_end() {
+    let mut program_test = ProgramTest::new(
+        "governance",
+        governance::id(),
+        solana_program_test::processor!(governance_processor),
+    );
+
+    let admin = Keypair::new();
+    let voter = Keypair::new();
+    let config_pda = add_governance_config(&mut program_test, admin.pubkey(), 1_000, 86_400, 172_800, false);
+
+    let nonce = 1u64;
+    let creator = Keypair::new();
+    let (proposal_pda, _) = Pubkey::find_program_address(
+        &[b"proposal", creator.pubkey().as_ref(), &nonce.to_le_bytes()],
+        &governance::id(),
+    );
+    add_proposal(
+        &mut program_test,
+        proposal_pda,
+        Proposal {
+            creator: creator.pubkey(),
+            nonce,
+            title: "Proposal".to_string(),
+            description: "Description".to_string(),
+            for_votes: 0,
+            against_votes: 0,
+            timelock_eta: 0,
+            voting_end_slot: 1_000,
+            executed: false,
+            action: ProposalAction::Signal,
+        },
+    );
+
+    let xgt_mint = XGT_MINT;
+    program_test.add_account(
+        xgt_mint,
+        Account {
+            lamports: 1_000_000,
+            data: mint_data(admin.pubkey()),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    // Still locked now, but withdrawable (and re-stakeable elsewhere) before voting ends
+    let voter_stake = add_xgt_stake(
+        &mut program_test,
+        sample_xgt_stake(voter.pubkey(), xgt_mint, 2_000, MAX_STAKE_LOCK_SLOTS / 2, 500),
+    );
+
+    let context = program_test.start_with_context().await;
+    let fund_tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&context.payer.pubkey(), &voter.pubkey(), 1_000_000_000)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(fund_tx).await.unwrap();
+
+    let (vote_record_pda, _) = Pubkey::find_program_address(
+        &[b"vote", proposal_pda.as_ref(), voter.pubkey().as_ref()],
+        &governance::id(),
+    );
+    let ix = Instruction {
+        program_id: governance::id(),
+        accounts: governance::accounts::Vote {
+            proposal: proposal_pda,
+            vote_record: vote_record_pda,
+            voter: voter.pubkey(),
+            stake: voter_stake,
+            xgt_mint,
+            system_program: system_program::id(),
+            governance_config: config_pda,
+        }
+        .to_account_metas(None),
+        data: governance::instruction::Vote { support: true }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&voter.pubkey()), &[&voter], context.last_blockhash);
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("stake unlocking mid-vote should not vote");
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => assert_eq!(code, u32::from(GovernanceError::StakeUnlocksBeforeVotingEnd)),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn test_boosted_weight_scales_with_lock_duration() {
+    let amount = 1_000_000u64;
+    assert_eq!(boosted_weight(amount, 0), Some(amount));
+    assert_eq!(boosted_weight(amount, MAX_STAKE_LOCK_SLOTS / 4), Some(1_250_000));
+    assert_eq!(boosted_weight(amount, MAX_STAKE_LOCK_SLOTS / 2), Some(1_500_000));
+    assert_eq!(boosted_weight(amount, MAX_STAKE_LOCK_SLOTS), Some(2_000_000));
+    // Locks beyond the maximum earn no extra boost
+    assert_eq!(boosted_weight(amount, MAX_STAKE_LOCK_SLOTS * 2), Some(2_000_000));
+    assert_eq!(boosted_weight(u64::MAX, MAX_STAKE_LOCK_SLOTS), None);
+
+    let stake = sample_xgt_stake(Pubkey::new_unique(), Pubkey::new_unique(), amount, MAX_STAKE_LOCK_SLOTS, 100);
+    assert_eq!(stake.voting_weight(99), 2_000_000);
+    assert_eq!(stake.voting_weight(100), 0);
+}
+
+#[test]
+fn test_proposal_threshold_requires_locked_stake() {
+    let mut config = GovernanceConfig {
+        quorum_votes: 1_000,
+        voting_period: 86_400,
+        timelock_delay: 172_800,
+        proposal_count: 0,
+        admin_authority: Pubkey::new_unique(),
+        paused: false,
+        quorum_multiplier_bps: [QUORUM_MULTIPLIER_BASE_BPS; ProposalActionKind::COUNT],
+        xgt_mint: XGT_MINT,
+        proposal_threshold: 1_000,
+        version: governance::STATE_VERSION,
+    };
+    let creator = Pubkey::new_unique();
+    let below = sample_xgt_stake(creator, XGT_MINT, 999, MAX_STAKE_LOCK_SLOTS, 100);
+    let above = sample_xgt_stake(creator, XGT_MINT, 1_500, MAX_STAKE_LOCK_SLOTS, 100);
+
+    assert!(!config.meets_proposal_threshold(None, 50));
+    assert!(!config.meets_proposal_threshold(Some(&below), 50));
+    assert!(config.meets_proposal_threshold(Some(&above), 50));
+    // Unlocked stake no longer counts
+    assert!(!config.meets_proposal_threshold(Some(&above), 100));
+
+    config.proposal_threshold = 0;
+    assert!(config.meets_proposal_threshold(None, 50));
+}
+
+#[tokio::test]
+async fn test_unstake_rejected_while_locked() {
+    let mut program_test = ProgramTest::new(
+        "governance",
+        governance::id(),
+        solana_program_test::processor!(governance_processor),
+    );
+
+    let owner = Keypair::new();
+    let xgt_mint = Pubkey::new_unique();
+    let stake_pda = add_xgt_stake(
+        &mut program_test,
+        sample_xgt_stake(owner.pubkey(), xgt_mint, 5_000, MAX_STAKE_LOCK_SLOTS, u64::MAX),
+    );
+    let stake_vault = get_associated_token_address(&stake_pda, &xgt_mint);
+    let user_xgt_account = Pubkey::new_unique();
+    for (address, token_owner, amount) in [(stake_vault, stake_pda, 5_000), (user_xgt_account, owner.pubkey(), 0)] {
+        program_test.add_account(
+            address,
+            Account {
+                lamports: 1_000_000,
+                data: token_account_data(xgt_mint, token_owner, amount),
+                owner: spl_token::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let context = program_test.start_with_context().await;
+    let fund_tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&context.payer.pubkey(), &owner.pubkey(), 1_000_000_000)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(fund_tx).await.unwrap();
+
+    let ix = Instruction {
+        program_id: governance::id(),
+        accounts: governance::accounts::UnstakeXgt {
+            stake: stake_pda,
+            stake_vault,
+            user_xgt_account,
+            owner: owner.pubkey(),
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: governance::instruction::UnstakeXgt {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&owner.pubkey()), &[&owner], context.last_blockhash);
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("locked stake should not unstake");
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => assert_eq!(code, u32::from(GovernanceError::StakeLocked)),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
 #[tokio::test]
 async fn test_queue_execution_timelock() {
     let mut program_test = ProgramTest::new(
@@ -376,12 +711,23 @@ async fn test_queue_execution_timelock() {
     );
 
     let (config_pda, _) = Pubkey::find_program_address(&[b"governance_config"], &governance::id());
+    program_test.add_account(
+        XGT_MINT,
+        Account {
+            lamports: 1_000_000,
+            data: mint_data(Pubkey::new_unique()),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
 
     let mut context = program_test.start_with_context().await;
     let short_ix = Instruction {
         program_id: governance::id(),
         accounts: governance::accounts::InitializeGovernance {
             governance_config: config_pda,
+            xgt_mint: XGT_MINT,
             payer: context.payer.pubkey(),
             system_program: system_program::id(),
         }
@@ -420,6 +766,7 @@ async fn test_queue_execution_timelock() {
         program_id: governance::id(),
         accounts: governance::accounts::InitializeGovernance {
             governance_config: config_pda,
+            xgt_mint: XGT_MINT,
             payer: context.payer.pubkey(),
             system_program: system_program::id(),
         }
@@ -481,7 +828,9 @@ async fn test_execute_proposal_quorum() {
             for_votes: 500,
             against_votes: 0,
             timelock_eta: 0,
+            voting_end_slot: 0,
             executed: false,
+            action: ProposalAction::Signal,
         },
     );
 
@@ -501,7 +850,9 @@ async fn test_execute_proposal_quorum() {
             for_votes: 1_500,
             against_votes: 100,
             timelock_eta: 0,
+            voting_end_slot: 0,
             executed: false,
+            action: ProposalAction::Signal,
         },
     );
 
@@ -523,6 +874,7 @@ async fn test_execute_proposal_quorum() {
         proposal: low_proposal_pda,
         governance_config: config_pda,
         executor: executor.pubkey(),
+        governance_authority: governance_authority_pda(),
     };
     let low_ix = Instruction {
         program_id: governance::id(),
@@ -555,6 +907,7 @@ async fn test_execute_proposal_quorum() {
         proposal: high_proposal_pda,
         governance_config: config_pda,
         executor: executor.pubkey(),
+        governance_authority: governance_authority_pda(),
     };
     let high_ix = Instruction {
         program_id: governance::id(),
@@ -580,6 +933,112 @@ async fn test_execute_proposal_quorum() {
     assert!(proposal.executed);
 }
 
+#[tokio::test]
+async fn test_high_impact_proposal_requires_escalated_quorum() {
+    let mut program_test = ProgramTest::new(
+        "governance",
+        governance::id(),
+        solana_program_test::processor!(governance_processor),
+    );
+
+    let admin = Keypair::new();
+    let (config_pda, _) = Pubkey::find_program_address(&[b"governance_config"], &governance::id());
+    let mut quorum_multiplier_bps = [QUORUM_MULTIPLIER_BASE_BPS; ProposalActionKind::COUNT];
+    quorum_multiplier_bps[ProposalActionKind::Pause as usize] = 20_000;
+    program_test.add_account(
+        config_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&GovernanceConfig {
+                quorum_votes: 1_000,
+                voting_period: 86_400,
+                timelock_delay: 172_800,
+                proposal_count: 0,
+                admin_authority: admin.pubkey(),
+                paused: false,
+                quorum_multiplier_bps,
+                xgt_mint: XGT_MINT,
+                proposal_threshold: 0,
+                version: governance::STATE_VERSION,
+            }),
+            owner: governance::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Both proposals carry 1_500 votes: enough for the base quorum, not for 2x
+    let creator = Keypair::new();
+    let proposals = [
+        (1u64, ProposalAction::Signal),
+        (2u64, ProposalAction::Pause { target: PauseTarget::All }),
+    ];
+    let mut proposal_pdas = Vec::new();
+    for (nonce, action) in proposals {
+        let (proposal_pda, _) = Pubkey::find_program_address(
+            &[b"proposal", creator.pubkey().as_ref(), &nonce.to_le_bytes()],
+            &governance::id(),
+        );
+        add_proposal(
+            &mut program_test,
+            proposal_pda,
+            Proposal {
+                creator: creator.pubkey(),
+                nonce,
+                title: "Proposal".to_string(),
+                description: "Proposal".to_string(),
+                for_votes: 1_500,
+                against_votes: 0,
+                timelock_eta: 0,
+                voting_end_slot: 0,
+                executed: false,
+                action,
+            },
+        );
+        proposal_pdas.push(proposal_pda);
+    }
+
+    let context = program_test.start_with_context().await;
+    let queue_tx = |proposal: Pubkey| {
+        let ix = Instruction {
+            program_id: governance::id(),
+            accounts: governance::accounts::QueueExecution {
+                proposal,
+                governance_config: config_pda,
+            }
+            .to_account_metas(None),
+            data: governance::instruction::QueueExecution {}.data(),
+        };
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        )
+    };
+
+    // Routine proposal passes at the base quorum
+    context
+        .banks_client
+        .process_transaction(queue_tx(proposal_pdas[0]))
+        .await
+        .unwrap();
+
+    // High-impact proposal needs 2_000 votes
+    let err = context
+        .banks_client
+        .process_transaction(queue_tx(proposal_pdas[1]))
+        .await
+        .expect_err("pause proposal below escalated quorum should fail");
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => assert_eq!(code, u32::from(GovernanceError::QuorumNotReached)),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
 #[tokio::test]
 async fn test_execute_proposal_authorization() {
     let mut program_test = ProgramTest::new(
@@ -609,7 +1068,9 @@ async fn test_execute_proposal_authorization() {
             for_votes: 1_500,
             against_votes: 0,
             timelock_eta: 0,
+            voting_end_slot: 0,
             executed: false,
+            action: ProposalAction::Signal,
         },
     );
 
@@ -631,6 +1092,7 @@ async fn test_execute_proposal_authorization() {
         proposal: proposal_pda,
         governance_config: config_pda,
         executor: executor.pubkey(),
+        governance_authority: governance_authority_pda(),
     };
     let ix = Instruction {
         program_id: governance::id(),
@@ -779,6 +1241,7 @@ async fn test_pause_governance_operations() {
         proposal: proposal_pda,
         governance_config: config_pda,
         creator: creator.pubkey(),
+        creator_stake: None,
         system_program: system_program::id(),
     };
     let create_ix = Instruction {
@@ -789,6 +1252,7 @@ async fn test_pause_governance_operations() {
             title: "Paused".to_string(),
             description: "Paused".to_string(),
             eta: 0,
+            action: ProposalAction::Signal,
         }
         .data(),
     };
@@ -814,3 +1278,272 @@ async fn test_pause_governance_operations() {
         other => panic!("unexpected error: {other:?}"),
     }
 }
+
+fn add_pause_proposal(
+    program_test: &mut ProgramTest,
+    creator: Pubkey,
+    nonce: u64,
+    target: PauseTarget,
+) -> Pubkey {
+    let (proposal_pda, _) = Pubkey::find_program_address(
+        &[b"proposal", creator.as_ref(), &nonce.to_le_bytes()],
+        &governance::id(),
+    );
+    add_proposal(
+        program_test,
+        proposal_pda,
+        Proposal {
+            creator,
+            nonce,
+            title: "Pause".to_string(),
+            description: "Pause".to_string(),
+            for_votes: 1_500,
+            against_votes: 0,
+            timelock_eta: 0,
+            voting_end_slot: 0,
+            executed: false,
+            action: ProposalAction::Pause { target },
+        },
+    );
+    proposal_pda
+}
+
+fn add_governed_protocol_config(program_test: &mut ProgramTest) -> Pubkey {
+    let (protocol_config_pda, _) =
+        Pubkey::find_program_address(&[b"protocol_config"], &financing_engine::id());
+    program_test.add_account(
+        protocol_config_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&sample_protocol_config(governance_authority_pda(), false)),
+            owner: financing_engine::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    protocol_config_pda
+}
+
+fn execute_pause_ix(
+    proposal: Pubkey,
+    config: Pubkey,
+    executor: Pubkey,
+    target_program: Pubkey,
+    target_state: Pubkey,
+) -> Instruction {
+    let mut accounts = governance::accounts::ExecuteProposal {
+        proposal,
+        governance_config: config,
+        executor,
+        governance_authority: governance_authority_pda(),
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new_readonly(target_program, false));
+    accounts.push(AccountMeta::new(target_state, false));
+    Instruction {
+        program_id: governance::id(),
+        accounts,
+        data: governance::instruction::Execute {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn test_execute_targeted_pause_proposal() {
+    let mut program_test = ProgramTest::new(
+        "governance",
+        governance::id(),
+        solana_program_test::processor!(governance_processor),
+    );
+    add_financing_engine_program(&mut program_test);
+
+    let admin = Keypair::new();
+    let creator = Keypair::new();
+    let config_pda = add_governance_config(&mut program_test, admin.pubkey(), 1_000, 86_400, 172_800, false);
+    let proposal_pda = add_pause_proposal(&mut program_test, creator.pubkey(), 11, PauseTarget::Financing);
+    let protocol_config_pda = add_governed_protocol_config(&mut program_test);
+
+    let mut context = program_test.start_with_context().await;
+    let ix = execute_pause_ix(
+        proposal_pda,
+        config_pda,
+        context.payer.pubkey(),
+        financing_engine::id(),
+        protocol_config_pda,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let protocol_account = context
+        .banks_client
+        .get_account(protocol_config_pda)
+        .await
+        .expect("fetch protocol config")
+        .expect("protocol config exists");
+    let mut protocol_data = protocol_account.data.as_slice();
+    let protocol_config =
+        ProtocolConfig::try_deserialize(&mut protocol_data).expect("deserialize protocol config");
+    assert!(protocol_config.protocol_paused);
+
+    let proposal_account = context
+        .banks_client
+        .get_account(proposal_pda)
+        .await
+        .expect("fetch proposal")
+        .expect("proposal exists");
+    let mut proposal_data = proposal_account.data.as_slice();
+    let proposal = Proposal::try_deserialize(&mut proposal_data).expect("deserialize proposal");
+    assert!(proposal.executed);
+}
+
+#[tokio::test]
+async fn test_execute_pause_proposal_rejects_mismatched_program() {
+    let mut program_test = ProgramTest::new(
+        "governance",
+        governance::id(),
+        solana_program_test::processor!(governance_processor),
+    );
+
+    let admin = Keypair::new();
+    let creator = Keypair::new();
+    let config_pda = add_governance_config(&mut program_test, admin.pubkey(), 1_000, 86_400, 172_800, false);
+    let proposal_pda = add_pause_proposal(&mut program_test, creator.pubkey(), 12, PauseTarget::Financing);
+    let protocol_config_pda = add_governed_protocol_config(&mut program_test);
+
+    let mut context = program_test.start_with_context().await;
+    // Financing target paired with the LP vault program id
+    let ix = execute_pause_ix(
+        proposal_pda,
+        config_pda,
+        context.payer.pubkey(),
+        lp_vault::id(),
+        protocol_config_pda,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("mismatched pause program should error");
+    let expected = u32::from(GovernanceError::InvalidPauseTargetAccounts);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => {
+            assert_eq!(code, expected);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_governance_from_pre_version_layout() {
+    let mut program_test = ProgramTest::new(
+        "governance",
+        governance::id(),
+        solana_program_test::processor!(governance_processor),
+    );
+
+    let admin = Keypair::new();
+    let attacker = Keypair::new();
+    let (config_pda, _) = Pubkey::find_program_address(&[b"governance_config"], &governance::id());
+    let space = 8 + GovernanceConfig::LEN;
+    add_baseline_layout_account::<GovernanceConfig, _>(
+        &mut program_test,
+        config_pda,
+        &baseline::GovernanceConfig {
+            quorum_votes: 1_000,
+            voting_period: 86_400,
+            timelock_delay: 172_800,
+            proposal_count: 7,
+            admin_authority: admin.pubkey(),
+            paused: false,
+        },
+        8 + baseline::GovernanceConfig::LEN,
+        space,
+        governance::id(),
+    );
+    program_test.add_account(
+        XGT_MINT,
+        Account {
+            lamports: 1_000_000,
+            data: mint_data(admin.pubkey()),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let migrate_ix = |admin_authority: Pubkey| Instruction {
+        program_id: governance::id(),
+        accounts: governance::accounts::MigrateGovernanceVersion {
+            governance_config: config_pda,
+            admin_authority,
+            xgt_mint: XGT_MINT,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: governance::instruction::MigrateGovernanceVersion {}.data(),
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_ix(attacker.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &attacker],
+        context.last_blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("non-admin should not migrate the governance config");
+    let expected = u32::from(GovernanceError::Unauthorized);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => {
+            assert_eq!(code, expected);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_ix(admin.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("admin migrates the governance config");
+
+    let account = context
+        .banks_client
+        .get_account(config_pda)
+        .await
+        .unwrap()
+        .expect("governance config account");
+    assert_eq!(account.data.len(), space);
+    let config = GovernanceConfig::try_deserialize(&mut account.data.as_slice()).expect("deserialize config");
+    assert_eq!(config.version, governance::STATE_VERSION);
+    assert_eq!(config.proposal_count, 7);
+    assert_eq!(config.admin_authority, admin.pubkey());
+    // Fields added since the baseline get explicit values; the mint comes from the admin
+    assert_eq!(config.xgt_mint, XGT_MINT);
+    assert_eq!(config.quorum_multiplier_bps, [QUORUM_MULTIPLIER_BASE_BPS; ProposalActionKind::COUNT]);
+    assert_eq!(config.proposal_threshold, 0);
+}
@@ -5,14 +5,26 @@ use anchor_lang::InstructionData;
 use anchor_lang::ToAccountMetas;
 use anchor_spl::associated_token::ID as ASSOCIATED_TOKEN_PROGRAM_ID;
 use anchor_spl::token::spl_token;
-use common::setup::{mint_data, oracle_sources, token_account_data, MIN_COLLATERAL_USD, MIN_FINANCING_AMOUNT};
-use financing_engine::{FinancingState, PositionStatus, ProtocolConfig, UserPositionCounter};
-use governance::{GovernanceConfig, Proposal, VoteRecord};
+use common::setup::{
+    add_lp_position, governance_authority_pda, governance_stake_pda, mint_data, oracle_sources, sample_asset_exposure, sample_financing_state,
+    sample_position_counter, sample_protocol_config, sample_protocol_stats, sample_xgt_stake,
+    token_account_data,
+    MIN_COLLATERAL_USD, MIN_FINANCING_AMOUNT,
+};
+use financing_engine::{
+    derive_position_pda, AssetExposure, AssetPriceConfig, FinancingState, PositionStatus,
+    ProtocolConfig, ProtocolStats, UserPositionCounter, ASSET_EXPOSURE_SEED,
+    ASSET_PRICE_CONFIG_SEED, ASSET_RISK_PARAMS_SEED, PROTOCOL_STATS_SEED,
+};
+use governance::{
+    GovernanceConfig, Proposal, ProposalAction, ProposalActionKind, VoteRecord, QUORUM_MULTIPLIER_BASE_BPS,
+};
 use liquidation_engine::LiquidationAuthority;
 use lp_vault::LPVaultState;
 use oracle_framework::OracleState;
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
+use solana_program_pack::Pack;
 use solana_program_test::{BanksClientError, ProgramTest};
 use solana_sdk::account::Account;
 use solana_sdk::bpf_loader;
@@ -22,7 +34,7 @@ use solana_sdk::signature::{Keypair, Signer};
 use solana_sdk::system_instruction;
 use solana_sdk::transaction::TransactionError;
 use solana_sdk::transaction::Transaction;
-use treasury_engine::Treasury;
+use treasury_engine::{CompoundDestination, Treasury};
 
 fn serialize_anchor_account<T: AccountSerialize>(data: &T) -> Vec<u8> {
     let mut buf = Vec::new();
@@ -146,17 +158,12 @@ fn associated_token_address(owner: Pubkey, mint: Pubkey) -> Pubkey {
 }
 
 #[tokio::test]
-async fn test_close_at_maturity_rejects_invalid_vault_financed_owner() {
+async fn test_close_at_maturity_rejects_invalid_protocol_usdc_owner() {
     let mut program_test = ProgramTest::new(
         "financing_engine",
         financing_engine::id(),
         solana_program_test::processor!(financing_engine_processor),
     );
-    program_test.add_program(
-        "lp_vault",
-        lp_vault::id(),
-        solana_program_test::processor!(lp_vault_processor),
-    );
     program_test.add_program(
         "spl_token",
         spl_token::id(),
@@ -166,12 +173,15 @@ async fn test_close_at_maturity_rejects_invalid_vault_financed_owner() {
     let user = Keypair::new();
     let admin = Keypair::new();
     let collateral_mint = Pubkey::new_unique();
-    let financed_mint = Pubkey::new_unique();
+    let usdc_mint = Pubkey::new_unique();
 
-    let (state_pda, _) = Pubkey::find_program_address(
-        &[b"financing", user.pubkey().as_ref(), collateral_mint.as_ref()],
+    let (state_pda, _) = derive_position_pda(&user.pubkey(), 0);
+    let (asset_exposure_pda, _) = Pubkey::find_program_address(
+        &[ASSET_EXPOSURE_SEED, Pubkey::default().as_ref()],
         &financing_engine::id(),
     );
+    let (protocol_stats_pda, _) =
+        Pubkey::find_program_address(&[PROTOCOL_STATS_SEED], &financing_engine::id());
     let (position_counter_pda, _) = Pubkey::find_program_address(
         &[b"position_counter", user.pubkey().as_ref()],
         &financing_engine::id(),
@@ -184,32 +194,17 @@ async fn test_close_at_maturity_rejects_invalid_vault_financed_owner() {
         &[b"vault_authority"],
         &financing_engine::id(),
     );
-    let (vault_collateral_ata, _) = Pubkey::find_program_address(
-        &[b"vault_collateral", collateral_mint.as_ref()],
-        &financing_engine::id(),
-    );
-    let (user_collateral_ata, _) = Pubkey::find_program_address(
-        &[b"user_collateral", user.pubkey().as_ref(), collateral_mint.as_ref()],
-        &financing_engine::id(),
-    );
-    let (vault_financed_ata, _) = Pubkey::find_program_address(
-        &[b"vault_financed", financed_mint.as_ref()],
-        &financing_engine::id(),
-    );
-    let (user_financed_ata, _) = Pubkey::find_program_address(
-        &[b"user_financed", user.pubkey().as_ref(), financed_mint.as_ref()],
-        &financing_engine::id(),
-    );
-    let (lp_vault_state, _) = Pubkey::find_program_address(&[b"lp_vault"], &lp_vault::id());
+    let vault_collateral_ata = associated_token_address(vault_authority_pda, collateral_mint);
+    let user_collateral_ata = associated_token_address(user.pubkey(), collateral_mint);
+    let user_usdc_ata = associated_token_address(user.pubkey(), usdc_mint);
+    // Repayment destination is not owned by the vault authority.
+    let protocol_usdc_ata = Pubkey::new_unique();
 
     program_test.add_account(
         protocol_config_pda,
         Account {
             lamports: 1_000_000,
-            data: serialize_anchor_account(&ProtocolConfig {
-                admin_authority: admin.pubkey(),
-                protocol_paused: false,
-            }),
+            data: serialize_anchor_account(&sample_protocol_config(admin.pubkey(), false)),
             owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
@@ -219,24 +214,13 @@ async fn test_close_at_maturity_rejects_invalid_vault_financed_owner() {
         state_pda,
         Account {
             lamports: 1_000_000,
-            data: serialize_anchor_account(&FinancingState {
-                user_pubkey: user.pubkey(),
+            data: serialize_anchor_account(&sample_financing_state(
+                user.pubkey(),
+                0,
                 collateral_mint,
-                collateral_amount: 5_000,
-                collateral_usd_value: 100_000_000,
-                financing_amount: 10_000,
-                initial_ltv: 5_000,
-                max_ltv: 8_000,
-                term_start: 0,
-                term_end: 0,
-                fee_schedule: 0,
-                carry_enabled: false,
-                liquidation_threshold: 0,
-                oracle_sources: Vec::new(),
-                delegated_settlement_authority: Pubkey::default(),
-                delegated_liquidation_authority: Pubkey::default(),
-                position_status: PositionStatus::Active,
-            }),
+                5_000,
+                10_000,
+            )),
             owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
@@ -246,44 +230,34 @@ async fn test_close_at_maturity_rejects_invalid_vault_financed_owner() {
         position_counter_pda,
         Account {
             lamports: 1_000_000,
-            data: serialize_anchor_account(&UserPositionCounter {
-                user: user.pubkey(),
-                open_positions: 1,
-            }),
+            data: serialize_anchor_account(&sample_position_counter(user.pubkey(), 1)),
             owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        lp_vault_state,
+        asset_exposure_pda,
         Account {
             lamports: 1_000_000,
-            data: serialize_anchor_account(&LPVaultState {
-                authority: admin.pubkey(),
-                paused: false,
-                vault_usdc_balance: 0,
-                locked_for_financing: 10_000,
-                total_shares: 0,
-                utilization: 0,
-            }),
-            owner: lp_vault::id(),
+            data: serialize_anchor_account(&sample_asset_exposure(Pubkey::default(), 0, 10_000)),
+            owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        collateral_mint,
+        protocol_stats_pda,
         Account {
             lamports: 1_000_000,
-            data: mint_data(admin.pubkey()),
-            owner: spl_token::id(),
+            data: serialize_anchor_account(&sample_protocol_stats(10_000)),
+            owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        financed_mint,
+        collateral_mint,
         Account {
             lamports: 1_000_000,
             data: mint_data(admin.pubkey()),
@@ -293,51 +267,51 @@ async fn test_close_at_maturity_rejects_invalid_vault_financed_owner() {
         },
     );
     program_test.add_account(
-        vault_collateral_ata,
+        usdc_mint,
         Account {
             lamports: 1_000_000,
-            data: token_account_data(collateral_mint, vault_authority_pda, 5_000),
+            data: mint_data(admin.pubkey()),
             owner: spl_token::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        user_collateral_ata,
+        vault_collateral_ata,
         Account {
             lamports: 1_000_000,
-            data: token_account_data(collateral_mint, user.pubkey(), 0),
+            data: token_account_data(collateral_mint, vault_authority_pda, 5_000),
             owner: spl_token::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        vault_financed_ata,
+        user_collateral_ata,
         Account {
             lamports: 1_000_000,
-            data: token_account_data(financed_mint, vault_authority_pda, 0),
+            data: token_account_data(collateral_mint, user.pubkey(), 0),
             owner: spl_token::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        user_financed_ata,
+        user_usdc_ata,
         Account {
             lamports: 1_000_000,
-            data: token_account_data(financed_mint, user.pubkey(), 10_000),
+            data: token_account_data(usdc_mint, user.pubkey(), 10_000),
             owner: spl_token::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        vault_authority_pda,
+        protocol_usdc_ata,
         Account {
             lamports: 1_000_000,
-            data: vec![],
-            owner: financing_engine::id(),
+            data: token_account_data(usdc_mint, user.pubkey(), 0),
+            owner: spl_token::id(),
             executable: false,
             rent_epoch: 0,
         },
@@ -363,15 +337,21 @@ async fn test_close_at_maturity_rejects_invalid_vault_financed_owner() {
         vault_collateral_ata,
         user_collateral_ata,
         vault_authority: vault_authority_pda,
+        financed_commodity_mint: None,
+        vault_financed_commodity_ata: None,
+        user_financed_commodity_ata: None,
+        financed_token_program: None,
         receiver: user.pubkey(),
         position_counter: position_counter_pda,
         token_program: spl_token::id(),
-        lp_vault: lp_vault_state,
-        financed_mint,
-        vault_financed_ata,
-        user_financed_ata,
-        lp_vault_program: lp_vault::id(),
+        collateral_token_program: spl_token::id(),
+        usdc_mint,
+        user_usdc_ata,
+        protocol_usdc_ata,
+        markup_destination_ata: None,
         protocol_config: protocol_config_pda,
+        asset_exposure: asset_exposure_pda,
+        protocol_stats: protocol_stats_pda,
     };
     let ix = Instruction {
         program_id: financing_engine::id(),
@@ -386,7 +366,7 @@ async fn test_close_at_maturity_rejects_invalid_vault_financed_owner() {
         context.last_blockhash,
     );
     let result = context.banks_client.process_transaction(tx).await;
-    let err = result.expect_err("invalid vault owner should fail");
+    let err = result.expect_err("invalid protocol usdc owner should fail");
     match err {
         BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
             assert_eq!(code, 2003, "unexpected error code");
@@ -401,11 +381,23 @@ async fn test_full_position_lifecycle() {
     let user = Keypair::new();
     let admin = Keypair::new();
     let collateral_mint = Pubkey::new_unique();
-    let financed_mint = Pubkey::new_unique();
-    let oracle_accounts = Pubkey::new_unique();
+    let usdc_mint = Pubkey::new_unique();
+    let financed_asset_mint: Pubkey = "EeoqCfDd2x5UaD21q2yam2QtBaHQxDzA9GrLyFBJkKEA".parse().unwrap();
 
-    let (state_pda, _) = Pubkey::find_program_address(
-        &[b"financing", user.pubkey().as_ref(), collateral_mint.as_ref()],
+    let (oracle_pda, _) = Pubkey::find_program_address(&[b"oracle"], &oracle_framework::id());
+    let (state_pda, _) = derive_position_pda(&user.pubkey(), 0);
+    let (asset_exposure_pda, _) = Pubkey::find_program_address(
+        &[ASSET_EXPOSURE_SEED, financed_asset_mint.as_ref()],
+        &financing_engine::id(),
+    );
+    let (protocol_stats_pda, _) =
+        Pubkey::find_program_address(&[PROTOCOL_STATS_SEED], &financing_engine::id());
+    let (asset_risk_params_pda, _) = Pubkey::find_program_address(
+        &[ASSET_RISK_PARAMS_SEED, collateral_mint.as_ref()],
+        &financing_engine::id(),
+    );
+    let (asset_price_config_pda, _) = Pubkey::find_program_address(
+        &[ASSET_PRICE_CONFIG_SEED, financed_asset_mint.as_ref()],
         &financing_engine::id(),
     );
     let (position_counter_pda, _) = Pubkey::find_program_address(
@@ -420,12 +412,12 @@ async fn test_full_position_lifecycle() {
         &[b"vault_authority"],
         &financing_engine::id(),
     );
-    let (lp_vault_state, _) = Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
 
     let vault_collateral_ata = associated_token_address(vault_authority_pda, collateral_mint);
-    let user_financed_ata = associated_token_address(user.pubkey(), financed_mint);
+    let protocol_usdc_ata = associated_token_address(vault_authority_pda, usdc_mint);
+    let user_financed_ata = associated_token_address(user.pubkey(), financed_asset_mint);
     let user_collateral_ata = Pubkey::new_unique();
-    let vault_financed_ata = Pubkey::new_unique();
+    let user_usdc_ata = Pubkey::new_unique();
 
     let collateral_amount = 5_000;
     let financing_amount = MIN_FINANCING_AMOUNT;
@@ -434,52 +426,39 @@ async fn test_full_position_lifecycle() {
         protocol_config_pda,
         Account {
             lamports: 1_000_000,
-            data: serialize_anchor_account(&ProtocolConfig {
-                admin_authority: admin.pubkey(),
-                protocol_paused: false,
-            }),
+            data: serialize_anchor_account(&sample_protocol_config(admin.pubkey(), false)),
             owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        lp_vault_state,
+        asset_price_config_pda,
         Account {
             lamports: 1_000_000,
-            data: serialize_anchor_account(&LPVaultState {
-                authority: admin.pubkey(),
-                paused: false,
-                vault_usdc_balance: 200_000_000,
-                locked_for_financing: 0,
-                total_shares: 0,
-                utilization: 0,
+            data: serialize_anchor_account(&AssetPriceConfig {
+                mint: financed_asset_mint,
+                price: 150_00000000,
+                decimals: 9,
+                last_updated: 0,
             }),
-            owner: lp_vault::id(),
-            executable: false,
-            rent_epoch: 0,
-        },
-    );
-    program_test.add_account(
-        collateral_mint,
-        Account {
-            lamports: 1_000_000,
-            data: mint_data(admin.pubkey()),
-            owner: spl_token::id(),
-            executable: false,
-            rent_epoch: 0,
-        },
-    );
-    program_test.add_account(
-        financed_mint,
-        Account {
-            lamports: 1_000_000,
-            data: mint_data(admin.pubkey()),
-            owner: spl_token::id(),
+            owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
+    for mint in [collateral_mint, usdc_mint, financed_asset_mint] {
+        program_test.add_account(
+            mint,
+            Account {
+                lamports: 1_000_000,
+                data: mint_data(admin.pubkey()),
+                owner: spl_token::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
     program_test.add_account(
         user_collateral_ata,
         Account {
@@ -501,47 +480,63 @@ async fn test_full_position_lifecycle() {
         },
     );
     program_test.add_account(
-        vault_financed_ata,
+        protocol_usdc_ata,
         Account {
             lamports: 1_000_000,
-            data: token_account_data(financed_mint, lp_vault_state, 200_000_000),
+            data: token_account_data(usdc_mint, vault_authority_pda, 0),
             owner: spl_token::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        user_financed_ata,
+        user_usdc_ata,
         Account {
             lamports: 1_000_000,
-            data: token_account_data(financed_mint, user.pubkey(), 0),
+            data: token_account_data(usdc_mint, user.pubkey(), financing_amount),
             owner: spl_token::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
     program_test.add_account(
-        vault_authority_pda,
+        user_financed_ata,
         Account {
             lamports: 1_000_000,
-            data: vec![],
-            owner: financing_engine::id(),
+            data: token_account_data(financed_asset_mint, user.pubkey(), 0),
+            owner: spl_token::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
+    // 5_000 base units of a 6-decimal mint worth MIN_COLLATERAL_USD => $20,000 per token.
     program_test.add_account(
-        oracle_accounts,
+        oracle_pda,
         Account {
             lamports: 1_000_000,
-            data: vec![],
+            data: serialize_anchor_account(&OracleState {
+                authority: admin.pubkey(),
+                protocol_admin: admin.pubkey(),
+                pyth_price: 0,
+                switchboard_price: 0,
+                synthetic_twap: 2_000_000_000_000,
+                last_twap_window: 0,
+                frozen_price: 0,
+                frozen_slot: 0,
+                last_update_slot: 0,
+                paused: false,
+                min_update_interval_slots: 0,
+                pyth_update_slot: 0,
+                switchboard_update_slot: 0,
+                version: oracle_framework::STATE_VERSION,
+            }),
             owner: oracle_framework::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
 
-    let mut context = program_test.start_with_context().await;
+    let context = program_test.start_with_context().await;
     let fund_user = system_instruction::transfer(
         &context.payer.pubkey(),
         &user.pubkey(),
@@ -566,34 +561,41 @@ async fn test_full_position_lifecycle() {
         user_collateral_ata,
         vault_collateral_ata,
         vault_authority: vault_authority_pda,
-        oracle_accounts,
+        oracle: oracle_pda,
         user: user.pubkey(),
         position_counter: position_counter_pda,
         token_program: spl_token::id(),
+        collateral_token_program: spl_token::id(),
+        financed_token_program: spl_token::id(),
         associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
         system_program: solana_sdk::system_program::id(),
-        lp_vault: lp_vault_state,
-        financed_mint,
-        vault_financed_ata,
+        usdc_mint,
+        protocol_usdc_ata,
+        financed_asset_mint,
         user_financed_ata,
-        lp_vault_program: lp_vault::id(),
         protocol_config: protocol_config_pda,
+        asset_exposure: asset_exposure_pda,
+        protocol_stats: protocol_stats_pda,
+        asset_risk_params: asset_risk_params_pda,
+        asset_price_config: asset_price_config_pda,
     };
     let open_ix = Instruction {
         program_id: financing_engine::id(),
         accounts: open_accounts.to_account_metas(None),
         data: financing_engine::instruction::InitializeFinancing {
+            position_index: 0,
             collateral_amount,
             collateral_usd_value: MIN_COLLATERAL_USD,
-            financing_amount,
+            financing_usdc_amount: financing_amount,
+            markup_bps: 0,
             initial_ltv: 5_000,
             max_ltv: 8_000,
             term_start: -100,
             term_end: -50,
-            fee_schedule: 0,
             carry_enabled: false,
             liquidation_threshold: 8_500,
             oracle_sources: oracle_sources(),
+            max_settlement_slippage_bps: 100,
         }
         .data(),
     };
@@ -605,6 +607,7 @@ async fn test_full_position_lifecycle() {
     );
     context.banks_client.process_transaction(open_tx).await.unwrap();
 
+    // +10% per-token move: the largest single update the deviation guard allows.
     let update_accounts = financing_engine::accounts::UpdateLtv {
         state: state_pda,
         protocol_config: protocol_config_pda,
@@ -614,7 +617,8 @@ async fn test_full_position_lifecycle() {
         program_id: financing_engine::id(),
         accounts: update_accounts.to_account_metas(None),
         data: financing_engine::instruction::UpdateLtv {
-            collateral_usd_value: 150_000_000,
+            collateral_usd_value: 110_000_000,
+            force: false,
         }
         .data(),
     };
@@ -626,21 +630,38 @@ async fn test_full_position_lifecycle() {
     );
     context.banks_client.process_transaction(update_tx).await.unwrap();
 
+    let state_account = context
+        .banks_client
+        .get_account(state_pda)
+        .await
+        .unwrap()
+        .expect("state account");
+    let state = deserialize_anchor_account::<FinancingState>(&state_account);
+    assert_eq!(state.position_status, PositionStatus::Active);
+    assert_eq!(state.collateral_usd_value, 110_000_000);
+    assert_eq!(state.deferred_payment_amount, financing_amount);
+
     let close_accounts = financing_engine::accounts::CloseAtMaturity {
         state: state_pda,
         collateral_mint,
         vault_collateral_ata,
         user_collateral_ata,
         vault_authority: vault_authority_pda,
+        financed_commodity_mint: None,
+        vault_financed_commodity_ata: None,
+        user_financed_commodity_ata: None,
+        financed_token_program: None,
         receiver: user.pubkey(),
         position_counter: position_counter_pda,
         token_program: spl_token::id(),
-        lp_vault: lp_vault_state,
-        financed_mint,
-        vault_financed_ata,
-        user_financed_ata,
-        lp_vault_program: lp_vault::id(),
+        collateral_token_program: spl_token::id(),
+        usdc_mint,
+        user_usdc_ata,
+        protocol_usdc_ata,
+        markup_destination_ata: None,
         protocol_config: protocol_config_pda,
+        asset_exposure: asset_exposure_pda,
+        protocol_stats: protocol_stats_pda,
     };
     let close_ix = Instruction {
         program_id: financing_engine::id(),
@@ -655,14 +676,9 @@ async fn test_full_position_lifecycle() {
     );
     context.banks_client.process_transaction(close_tx).await.unwrap();
 
-    let state_account = context
-        .banks_client
-        .get_account(state_pda)
-        .await
-        .unwrap()
-        .expect("state account");
-    let state = deserialize_anchor_account::<FinancingState>(&state_account);
-    assert_eq!(state.position_status, PositionStatus::Closed);
+    // Closing returns the position rent to the receiver.
+    let state_account = context.banks_client.get_account(state_pda).await.unwrap();
+    assert!(state_account.is_none());
 
     let counter_account = context
         .banks_client
@@ -672,16 +688,36 @@ async fn test_full_position_lifecycle() {
         .expect("counter account");
     let counter = deserialize_anchor_account::<UserPositionCounter>(&counter_account);
     assert_eq!(counter.open_positions, 0);
+    assert_eq!(counter.total_positions, 1);
 
-    let vault_account = context
+    let exposure_account = context
         .banks_client
-        .get_account(lp_vault_state)
+        .get_account(asset_exposure_pda)
         .await
         .unwrap()
-        .expect("vault account");
-    let vault = deserialize_anchor_account::<LPVaultState>(&vault_account);
-    assert_eq!(vault.locked_for_financing, 0);
-    assert_eq!(vault.vault_usdc_balance, 200_000_000);
+        .expect("asset exposure account");
+    let exposure = deserialize_anchor_account::<AssetExposure>(&exposure_account);
+    assert_eq!(exposure.financed_mint, financed_asset_mint);
+    assert_eq!(exposure.outstanding_financing, 0);
+
+    let stats_account = context
+        .banks_client
+        .get_account(protocol_stats_pda)
+        .await
+        .unwrap()
+        .expect("protocol stats account");
+    let stats = deserialize_anchor_account::<ProtocolStats>(&stats_account);
+    assert_eq!(stats.total_outstanding_debt, 0);
+
+    let protocol_usdc_account = context
+        .banks_client
+        .get_account(protocol_usdc_ata)
+        .await
+        .unwrap()
+        .expect("protocol usdc account");
+    let protocol_usdc =
+        spl_token::state::Account::unpack(&protocol_usdc_account.data).expect("unpack");
+    assert_eq!(protocol_usdc.amount, financing_amount);
 }
 
 #[tokio::test]
@@ -702,38 +738,20 @@ async fn test_liquidation_flow() {
         &[b"protocol_config"],
         &financing_engine::id(),
     );
-    let (state_pda, _) = Pubkey::find_program_address(
-        &[b"financing", user.pubkey().as_ref(), oracle_feed.as_ref()],
-        &financing_engine::id(),
-    );
+    let (state_pda, _) = derive_position_pda(&user.pubkey(), 0);
 
     let financing_state = FinancingState {
-        user_pubkey: user.pubkey(),
-        collateral_mint: oracle_feed,
-        collateral_amount: 0,
         collateral_usd_value: 200_000_000,
-        financing_amount: 150_000_000,
-        initial_ltv: 5_000,
-        max_ltv: 8_000,
-        term_start: 0,
-        term_end: 0,
-        fee_schedule: 0,
-        carry_enabled: false,
         liquidation_threshold: 8_000,
         oracle_sources: vec![oracle_authority.pubkey()],
-        delegated_settlement_authority: Pubkey::default(),
-        delegated_liquidation_authority: Pubkey::default(),
-        position_status: PositionStatus::Active,
+        ..sample_financing_state(user.pubkey(), 0, oracle_feed, 0, 150_000_000)
     };
 
     program_test.add_account(
         protocol_config_pda,
         Account {
             lamports: 1_000_000,
-            data: serialize_anchor_account(&ProtocolConfig {
-                admin_authority: admin.pubkey(),
-                protocol_paused: false,
-            }),
+            data: serialize_anchor_account(&sample_protocol_config(admin.pubkey(), false)),
             owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
@@ -764,6 +782,10 @@ async fn test_liquidation_flow() {
                 frozen_slot: 0,
                 last_update_slot: 0,
                 paused: false,
+                min_update_interval_slots: 0,
+                pyth_update_slot: 0,
+                switchboard_update_slot: 0,
+                version: oracle_framework::STATE_VERSION,
             }),
             owner: oracle_framework::id(),
             executable: false,
@@ -842,7 +864,7 @@ async fn test_liquidation_flow() {
     assert_eq!(oracle_state.pyth_price, 100_000_000);
 
     let ltv = financing_engine::ltv_model(
-        financing_state.financing_amount,
+        financing_state.deferred_payment_amount,
         oracle_state.pyth_price as u64,
     )
     .expect("ltv");
@@ -920,6 +942,17 @@ async fn test_lp_vault_flow() {
             data: serialize_anchor_account(&LPVaultState {
                 authority: user.pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
                 vault_usdc_balance: 0,
                 locked_for_financing: 0,
                 total_shares: 0,
@@ -991,7 +1024,8 @@ async fn test_lp_vault_flow() {
         },
     );
 
-    let mut context = program_test.start_with_context().await;
+    let lp_position = add_lp_position(&mut program_test, user.pubkey(), lp_vault::NAV_PRECISION);
+    let context = program_test.start_with_context().await;
     let fund_user = system_instruction::transfer(
         &context.payer.pubkey(),
         &user.pubkey(),
@@ -1011,6 +1045,7 @@ async fn test_lp_vault_flow() {
         user_lp_token_account,
         user_usdc_account,
         vault_usdc_account,
+        lp_position,
         user: user.pubkey(),
         token_program: spl_token::id(),
     };
@@ -1098,13 +1133,181 @@ async fn test_lp_vault_flow() {
     assert_eq!(final_vault.vault_usdc_balance, deposit_amount);
 }
 
+#[tokio::test]
+async fn test_fund_insurance_from_treasury() {
+    let mut program_test = integration_program_test();
+    let admin = Keypair::new();
+    let (vault_pda, _) = Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let (treasury_pda, _) = Pubkey::find_program_address(&[b"treasury"], &treasury_engine::id());
+    let usdc_mint = Pubkey::new_unique();
+    let treasury_usdc_account = Pubkey::new_unique();
+    let vault_usdc_account = associated_token_address(vault_pda, usdc_mint);
+
+    let reserve_amount = 50_000_000;
+    let insurance_amount = 20_000_000;
+
+    program_test.add_account(
+        vault_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                authority: admin.pubkey(),
+                paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
+                vault_usdc_balance: 0,
+                locked_for_financing: 0,
+                total_shares: 0,
+                utilization: 0,
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        treasury_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&Treasury {
+                admin: admin.pubkey(),
+                lp_contributed: 0,
+                co_financing_outstanding: 0,
+                base_fee_accrued: 0,
+                carry_accrued: 0,
+                compounded_xrs: 0,
+                paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Reserve,
+                reserve_balance: reserve_amount,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
+            }),
+            owner: treasury_engine::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        usdc_mint,
+        Account {
+            lamports: 1_000_000,
+            data: mint_data(admin.pubkey()),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        treasury_usdc_account,
+        Account {
+            lamports: 1_000_000,
+            data: token_account_data(usdc_mint, treasury_pda, reserve_amount),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        vault_usdc_account,
+        Account {
+            lamports: 1_000_000,
+            data: token_account_data(usdc_mint, vault_pda, 0),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    let fund_admin = system_instruction::transfer(
+        &context.payer.pubkey(),
+        &admin.pubkey(),
+        1_000_000_000,
+    );
+    let fund_tx = Transaction::new_signed_with_payer(
+        &[fund_admin],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(fund_tx).await.unwrap();
+
+    let accounts = treasury_engine::accounts::FundInsuranceFromTreasury {
+        treasury: treasury_pda,
+        admin_authority: admin.pubkey(),
+        co_signer: None,
+        usdc_mint,
+        treasury_usdc_account,
+        lp_vault: vault_pda,
+        vault_usdc_account,
+        lp_vault_program: lp_vault::id(),
+        token_program: spl_token::id(),
+    };
+    let ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: accounts.to_account_metas(None),
+        data: treasury_engine::instruction::FundInsuranceFromTreasury {
+            amount: insurance_amount,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let vault_account = context
+        .banks_client
+        .get_account(vault_pda)
+        .await
+        .unwrap()
+        .expect("vault account");
+    let vault_state = deserialize_anchor_account::<LPVaultState>(&vault_account);
+    assert_eq!(vault_state.insurance_balance, insurance_amount);
+    assert_eq!(vault_state.vault_usdc_balance, 0);
+
+    let treasury_account = context
+        .banks_client
+        .get_account(treasury_pda)
+        .await
+        .unwrap()
+        .expect("treasury account");
+    let treasury = deserialize_anchor_account::<Treasury>(&treasury_account);
+    assert_eq!(treasury.reserve_balance, reserve_amount - insurance_amount);
+
+    let vault_usdc = context
+        .banks_client
+        .get_account(vault_usdc_account)
+        .await
+        .unwrap()
+        .expect("vault usdc account");
+    let vault_usdc = spl_token::state::Account::unpack(&vault_usdc.data).expect("unpack");
+    assert_eq!(vault_usdc.amount, insurance_amount);
+}
+
 #[tokio::test]
 async fn test_governance_flow() {
     let mut program_test = integration_program_test();
     let creator = Keypair::new();
     let voter = Keypair::new();
     let xgt_mint = Pubkey::new_unique();
-    let user_xgt_account = Pubkey::new_unique();
+    let (stake_pda, _) = governance_stake_pda(voter.pubkey());
 
     let (governance_config_pda, _) =
         Pubkey::find_program_address(&[b"governance_config"], &governance::id());
@@ -1128,18 +1331,19 @@ async fn test_governance_flow() {
             rent_epoch: 0,
         },
     );
+    // Unboosted minimum lock: weight equals the 1_500 XGT staked
     program_test.add_account(
-        user_xgt_account,
+        stake_pda,
         Account {
             lamports: 1_000_000,
-            data: token_account_data(xgt_mint, voter.pubkey(), 1_500),
-            owner: spl_token::id(),
+            data: serialize_anchor_account(&sample_xgt_stake(voter.pubkey(), xgt_mint, 1_500, 1, u64::MAX)),
+            owner: governance::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
 
-    let mut context = program_test.start_with_context().await;
+    let context = program_test.start_with_context().await;
     let fund_tx = Transaction::new_signed_with_payer(
         &[
             system_instruction::transfer(&context.payer.pubkey(), &creator.pubkey(), 1_000_000_000),
@@ -1153,6 +1357,7 @@ async fn test_governance_flow() {
 
     let init_accounts = governance::accounts::InitializeGovernance {
         governance_config: governance_config_pda,
+        xgt_mint,
         payer: creator.pubkey(),
         system_program: solana_sdk::system_program::id(),
     };
@@ -1179,6 +1384,7 @@ async fn test_governance_flow() {
         proposal: proposal_pda,
         governance_config: governance_config_pda,
         creator: creator.pubkey(),
+        creator_stake: None,
         system_program: solana_sdk::system_program::id(),
     };
     let create_ix = Instruction {
@@ -1189,6 +1395,7 @@ async fn test_governance_flow() {
             title: "Raise LTV cap".to_string(),
             description: "Adjust risk limits".to_string(),
             eta: 0,
+            action: ProposalAction::Signal,
         }
         .data(),
     };
@@ -1204,7 +1411,7 @@ async fn test_governance_flow() {
         proposal: proposal_pda,
         vote_record: vote_record_pda,
         voter: voter.pubkey(),
-        user_xgt_account,
+        stake: stake_pda,
         xgt_mint,
         system_program: solana_sdk::system_program::id(),
         governance_config: governance_config_pda,
@@ -1243,6 +1450,7 @@ async fn test_governance_flow() {
         proposal: proposal_pda,
         governance_config: governance_config_pda,
         executor: creator.pubkey(),
+        governance_authority: governance_authority_pda(),
     };
     let execute_ix = Instruction {
         program_id: governance::id(),
@@ -1305,10 +1513,7 @@ async fn test_cross_program_circuit_breaker() {
         protocol_config_pda,
         Account {
             lamports: 1_000_000,
-            data: serialize_anchor_account(&ProtocolConfig {
-                admin_authority: admin.pubkey(),
-                protocol_paused: false,
-            }),
+            data: serialize_anchor_account(&sample_protocol_config(admin.pubkey(), false)),
             owner: financing_engine::id(),
             executable: false,
             rent_epoch: 0,
@@ -1321,6 +1526,17 @@ async fn test_cross_program_circuit_breaker() {
             data: serialize_anchor_account(&LPVaultState {
                 authority: admin.pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
                 vault_usdc_balance: 0,
                 locked_for_financing: 0,
                 total_shares: 0,
@@ -1342,6 +1558,10 @@ async fn test_cross_program_circuit_breaker() {
                 proposal_count: 0,
                 admin_authority: admin.pubkey(),
                 paused: false,
+                quorum_multiplier_bps: [QUORUM_MULTIPLIER_BASE_BPS; ProposalActionKind::COUNT],
+                xgt_mint: Pubkey::new_unique(),
+                proposal_threshold: 0,
+                version: governance::STATE_VERSION,
             }),
             owner: governance::id(),
             executable: false,
@@ -1363,6 +1583,10 @@ async fn test_cross_program_circuit_breaker() {
                 frozen_slot: 0,
                 last_update_slot: 0,
                 paused: false,
+                min_update_interval_slots: 0,
+                pyth_update_slot: 0,
+                switchboard_update_slot: 0,
+                version: oracle_framework::STATE_VERSION,
             }),
             owner: oracle_framework::id(),
             executable: false,
@@ -1381,6 +1605,14 @@ async fn test_cross_program_circuit_breaker() {
                 carry_accrued: 0,
                 compounded_xrs: 0,
                 paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Xrs,
+                reserve_balance: 0,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
             }),
             owner: treasury_engine::id(),
             executable: false,
@@ -1388,7 +1620,7 @@ async fn test_cross_program_circuit_breaker() {
         },
     );
 
-    let mut context = program_test.start_with_context().await;
+    let context = program_test.start_with_context().await;
     let fund_admin = system_instruction::transfer(
         &context.payer.pubkey(),
         &admin.pubkey(),
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::{AccountDeserialize, AccountSerialize, Pubkey};
 use anchor_lang::InstructionData;
 use anchor_lang::ToAccountMetas;
-use liquidation_engine::{LiquidationAuthority, LiquidationError};
+use liquidation_engine::{Keeper, LiquidationAuthority, LiquidationError, KEEPER_SEED};
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program_test::{BanksClientError, ProgramTest};
@@ -142,7 +142,6 @@ async fn test_snapshot_expiration() {
 
     context
         .warp_to_slot(frozen_slot + 101)
-        .await
         .expect("warp to future slot");
 
     let ix = Instruction {
@@ -413,3 +412,125 @@ async fn test_slippage_limits() {
     let authority = LiquidationAuthority::try_deserialize(&mut data_slice).expect("deserialize authority");
     assert!(!authority.executed);
 }
+
+fn add_keeper(program_test: &mut ProgramTest, authority: Pubkey, last_heartbeat_slot: u64) -> Pubkey {
+    let (keeper_pda, _) =
+        Pubkey::find_program_address(&[KEEPER_SEED, authority.as_ref()], &liquidation_engine::id());
+    let keeper = Keeper {
+        authority,
+        registered_slot: 0,
+        last_heartbeat_slot,
+    };
+    program_test.add_account(
+        keeper_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&keeper),
+            owner: liquidation_engine::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    keeper_pda
+}
+
+#[tokio::test]
+async fn test_keeper_heartbeat_updates_last_seen() {
+    let mut program_test = ProgramTest::new(
+        "liquidation_engine",
+        liquidation_engine::id(),
+        solana_program_test::processor!(liquidation_engine_processor),
+    );
+
+    let keeper = Keypair::new();
+    let keeper_pda = add_keeper(&mut program_test, keeper.pubkey(), 0);
+
+    let mut context = program_test.start_with_context().await;
+    context.warp_to_slot(50).expect("warp to future slot");
+
+    let accounts = liquidation_engine::accounts::KeeperHeartbeatCtx {
+        keeper: keeper_pda,
+        authority: keeper.pubkey(),
+    };
+    let ix = Instruction {
+        program_id: liquidation_engine::id(),
+        accounts: accounts.to_account_metas(None),
+        data: liquidation_engine::instruction::KeeperHeartbeat {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &keeper],
+        context.banks_client.get_latest_blockhash().await.unwrap(),
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(keeper_pda)
+        .await
+        .expect("get keeper account")
+        .expect("keeper account");
+    let mut data_slice: &[u8] = &account.data;
+    let refreshed = Keeper::try_deserialize(&mut data_slice).expect("deserialize keeper");
+    assert_eq!(refreshed.last_heartbeat_slot, 50);
+}
+
+#[tokio::test]
+async fn test_list_stale_keepers_rejects_foreign_accounts() {
+    let mut program_test = ProgramTest::new(
+        "liquidation_engine",
+        liquidation_engine::id(),
+        solana_program_test::processor!(liquidation_engine_processor),
+    );
+
+    let fresh_keeper = add_keeper(&mut program_test, Pubkey::new_unique(), 90);
+    let stale_keeper = add_keeper(&mut program_test, Pubkey::new_unique(), 0);
+    let impostor = Pubkey::new_unique();
+    program_test.add_account(
+        impostor,
+        Account {
+            lamports: 1_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    context.warp_to_slot(100).expect("warp to future slot");
+
+    let list_ix = |keepers: &[Pubkey]| Instruction {
+        program_id: liquidation_engine::id(),
+        accounts: keepers
+            .iter()
+            .map(|keeper| solana_sdk::instruction::AccountMeta::new_readonly(*keeper, false))
+            .collect(),
+        data: liquidation_engine::instruction::ListStaleKeepers { max_age_slots: 50 }.data(),
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[list_ix(&[fresh_keeper, stale_keeper])],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.banks_client.get_latest_blockhash().await.unwrap(),
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[list_ix(&[fresh_keeper, impostor])],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.banks_client.get_latest_blockhash().await.unwrap(),
+    );
+    let result = context.banks_client.process_transaction(tx).await;
+    let err = result.expect_err("non-keeper accounts should be rejected");
+    let expected = u32::from(LiquidationError::InvalidKeeperAccount);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, expected, "unexpected error code");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
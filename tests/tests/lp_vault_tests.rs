@@ -4,11 +4,14 @@ use anchor_lang::prelude::AccountSerialize;
 use anchor_lang::prelude::AccountDeserialize;
 use anchor_lang::InstructionData;
 use anchor_lang::ToAccountMetas;
+use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::spl_token;
-use common::setup::{mint_data, token_account_data};
-use lp_vault::{LPVaultState, VaultError};
+use common::setup::{add_baseline_layout_account, add_lp_position, baseline, mint_data, token_account_data};
+use lp_vault::{LPVaultState, LpPosition, VaultError};
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::program_option::COption;
+use solana_program_pack::Pack;
 use solana_program_test::{BanksClientError, ProgramTest};
 use solana_sdk::account::Account;
 use solana_sdk::bpf_loader;
@@ -82,6 +85,17 @@ async fn test_pause_vault_requires_authority() {
                 utilization: 0,
                 authority: admin.pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
             }),
             owner: lp_vault::id(),
             executable: false,
@@ -163,6 +177,17 @@ async fn test_allocate_financing_rejected_when_paused() {
                 utilization: 0,
                 authority: admin.pubkey(),
                 paused: true,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
             }),
             owner: lp_vault::id(),
             executable: false,
@@ -238,10 +263,22 @@ async fn test_initialize_vault() {
 
     let authority = Keypair::new();
     let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let lp_mint = solana_program::pubkey::Pubkey::new_unique();
+    program_test.add_account(
+        lp_mint,
+        Account {
+            lamports: 1_000_000,
+            data: mint_data(vault_pda),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
 
     let mut context = program_test.start_with_context().await;
     let accounts = lp_vault::accounts::InitializeVault {
         vault: vault_pda,
+        lp_token_mint: lp_mint,
         payer: context.payer.pubkey(),
         system_program: system_program::id(),
     };
@@ -270,6 +307,55 @@ async fn test_initialize_vault() {
     assert!(!vault_state.paused);
 }
 
+#[tokio::test]
+async fn test_initialize_vault_rejects_foreign_mint_authority() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+
+    let authority = Keypair::new();
+    let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let lp_mint = solana_program::pubkey::Pubkey::new_unique();
+    program_test.add_account(
+        lp_mint,
+        Account {
+            lamports: 1_000_000,
+            data: mint_data(authority.pubkey()),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    let accounts = lp_vault::accounts::InitializeVault {
+        vault: vault_pda,
+        lp_token_mint: lp_mint,
+        payer: context.payer.pubkey(),
+        system_program: system_program::id(),
+    };
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: accounts.to_account_metas(None),
+        data: lp_vault::instruction::InitializeVault {
+            authority: authority.pubkey(),
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("mint authority outside the vault should fail");
+    assert_vault_error(err, VaultError::InvalidMintAuthority);
+}
+
 #[tokio::test]
 async fn test_allocate_financing_liquidity_check() {
     let mut program_test =
@@ -294,6 +380,17 @@ async fn test_allocate_financing_liquidity_check() {
                 utilization: 0,
                 authority: admin.pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
             }),
             owner: lp_vault::id(),
             executable: false,
@@ -403,6 +500,17 @@ async fn test_release_financing_accounting() {
                 utilization: 0,
                 authority: Keypair::new().pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
             }),
             owner: lp_vault::id(),
             executable: false,
@@ -487,6 +595,17 @@ async fn test_write_off_bad_debt_authorization() {
                 utilization: 0,
                 authority: admin.pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
             }),
             owner: lp_vault::id(),
             executable: false,
@@ -550,6 +669,79 @@ async fn test_write_off_bad_debt_authorization() {
     assert_eq!(vault_state.locked_for_financing, 200);
 }
 
+#[tokio::test]
+async fn test_write_off_bad_debt_rejected_above_cap() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+
+    let admin = Keypair::new();
+    let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+
+    program_test.add_account(
+        vault_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                total_shares: 0,
+                vault_usdc_balance: 2_000,
+                locked_for_financing: 1_000,
+                utilization: 0,
+                authority: admin.pubkey(),
+                paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 500,
+                max_write_off_per_epoch: 800,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let write_off_tx = |bad_debt: u64, blockhash| {
+        let accounts = lp_vault::accounts::WriteOffBadDebt {
+            vault: vault_pda,
+            authority: admin.pubkey(),
+        };
+        let ix = Instruction {
+            program_id: lp_vault::id(),
+            accounts: accounts.to_account_metas(None),
+            data: lp_vault::instruction::WriteOffBadDebt {
+                financing_amount: 0,
+                bad_debt,
+            }
+            .data(),
+        };
+        Transaction::new_signed_with_payer(&[ix], Some(&context.payer.pubkey()), &[&context.payer, &admin], blockhash)
+    };
+
+    // Above the per-call cap
+    let tx = write_off_tx(600, context.last_blockhash);
+    let err = context.banks_client.process_transaction(tx).await.expect_err("write-off above per-call cap should fail");
+    assert_vault_error(err, VaultError::WriteOffCapExceeded);
+
+    let tx = write_off_tx(400, context.last_blockhash);
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // Within the per-call cap but past the per-epoch cap
+    let tx = write_off_tx(450, context.last_blockhash);
+    let err = context.banks_client.process_transaction(tx).await.expect_err("write-off above per-epoch cap should fail");
+    assert_vault_error(err, VaultError::WriteOffCapExceeded);
+
+    let vault_state = fetch_vault_state(&mut context, vault_pda).await;
+    assert_eq!(vault_state.vault_usdc_balance, 1_600);
+    assert_eq!(vault_state.written_off_this_epoch, 400);
+}
+
 #[tokio::test]
 async fn test_pause_vault_operations() {
     let mut program_test =
@@ -569,6 +761,17 @@ async fn test_pause_vault_operations() {
                 utilization: 0,
                 authority: admin.pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
             }),
             owner: lp_vault::id(),
             executable: false,
@@ -682,6 +885,17 @@ async fn test_share_price_calculation() {
                 utilization: 0,
                 authority: Keypair::new().pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
             }),
             owner: lp_vault::id(),
             executable: false,
@@ -739,6 +953,7 @@ async fn test_share_price_calculation() {
         },
     );
 
+    let lp_position = add_lp_position(&mut program_test, user.pubkey(), lp_vault::NAV_PRECISION);
     let mut context = program_test.start_with_context().await;
     let accounts = lp_vault::accounts::DepositUsdc {
         vault: vault_pda,
@@ -746,6 +961,7 @@ async fn test_share_price_calculation() {
         user_lp_token_account: user_lp_account,
         user_usdc_account,
         vault_usdc_account,
+        lp_position,
         user: user.pubkey(),
         token_program: spl_token::id(),
     };
@@ -798,6 +1014,17 @@ async fn test_share_price_calculation() {
                 utilization: 0,
                 authority: Keypair::new().pubkey(),
                 paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
             }),
             owner: lp_vault::id(),
             executable: false,
@@ -855,6 +1082,7 @@ async fn test_share_price_calculation() {
         },
     );
 
+    let lp_position = add_lp_position(&mut program_test, user.pubkey(), lp_vault::NAV_PRECISION);
     let mut context = program_test.start_with_context().await;
     let accounts = lp_vault::accounts::DepositUsdc {
         vault: vault_pda,
@@ -862,6 +1090,7 @@ async fn test_share_price_calculation() {
         user_lp_token_account: user_lp_account,
         user_usdc_account,
         vault_usdc_account,
+        lp_position,
         user: user.pubkey(),
         token_program: spl_token::id(),
     };
@@ -889,3 +1118,1197 @@ async fn test_share_price_calculation() {
         other => panic!("unexpected error: {other:?}"),
     }
 }
+
+struct InsuranceFixture {
+    vault: solana_program::pubkey::Pubkey,
+    usdc_mint: solana_program::pubkey::Pubkey,
+    funder_usdc_account: solana_program::pubkey::Pubkey,
+    vault_usdc_account: solana_program::pubkey::Pubkey,
+}
+
+/// Vault tracking 2,000 USDC (1,000 lent out) with an empty insurance buffer
+fn add_insurance_accounts(
+    program_test: &mut ProgramTest,
+    authority: solana_program::pubkey::Pubkey,
+    funder: solana_program::pubkey::Pubkey,
+    funder_usdc: u64,
+) -> InsuranceFixture {
+    let (vault, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let usdc_mint = solana_program::pubkey::Pubkey::new_unique();
+    let funder_usdc_account = solana_program::pubkey::Pubkey::new_unique();
+    let vault_usdc_account = get_associated_token_address(&vault, &usdc_mint);
+
+    add_spl_token_program(program_test);
+    program_test.add_account(
+        vault,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                total_shares: 2_000,
+                vault_usdc_balance: 2_000,
+                locked_for_financing: 1_000,
+                utilization: 5_000,
+                authority,
+                paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        usdc_mint,
+        Account {
+            lamports: 1_000_000,
+            data: mint_data(authority),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    for (account, owner, amount) in [(funder_usdc_account, funder, funder_usdc), (vault_usdc_account, vault, 2_000)] {
+        program_test.add_account(
+            account,
+            Account {
+                lamports: 1_000_000,
+                data: token_account_data(usdc_mint, owner, amount),
+                owner: spl_token::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    InsuranceFixture { vault, usdc_mint, funder_usdc_account, vault_usdc_account }
+}
+
+fn fund_insurance_ix(
+    fixture: &InsuranceFixture,
+    funder: solana_program::pubkey::Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: lp_vault::id(),
+        accounts: lp_vault::accounts::FundInsurance {
+            vault: fixture.vault,
+            usdc_mint: fixture.usdc_mint,
+            funder_usdc_account: fixture.funder_usdc_account,
+            vault_usdc_account: fixture.vault_usdc_account,
+            funder,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: lp_vault::instruction::FundInsurance { amount }.data(),
+    }
+}
+
+#[tokio::test]
+async fn test_fund_insurance_requires_authority_or_treasury() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let admin = Keypair::new();
+    let stranger = Keypair::new();
+    let fixture = add_insurance_accounts(&mut program_test, admin.pubkey(), stranger.pubkey(), 500);
+
+    let context = program_test.start_with_context().await;
+    let tx = Transaction::new_signed_with_payer(
+        &[fund_insurance_ix(&fixture, stranger.pubkey(), 500)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &stranger],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    assert_vault_error(err, VaultError::Unauthorized);
+}
+
+#[tokio::test]
+async fn test_fund_insurance_rejects_non_ata_vault_account() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let admin = Keypair::new();
+    let fixture = add_insurance_accounts(&mut program_test, admin.pubkey(), admin.pubkey(), 500);
+    // A vault-owned USDC account that isn't the vault's ATA
+    let stray_vault_account = solana_program::pubkey::Pubkey::new_unique();
+    program_test.add_account(
+        stray_vault_account,
+        Account {
+            lamports: 1_000_000,
+            data: token_account_data(fixture.usdc_mint, fixture.vault, 0),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    let fixture = InsuranceFixture { vault_usdc_account: stray_vault_account, ..fixture };
+    let tx = Transaction::new_signed_with_payer(
+        &[fund_insurance_ix(&fixture, admin.pubkey(), 500)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, anchor_lang::error::ErrorCode::ConstraintAssociated as u32);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_insurance_absorbs_bad_debt_write_off() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let admin = Keypair::new();
+    let fixture = add_insurance_accounts(&mut program_test, admin.pubkey(), admin.pubkey(), 300);
+
+    let mut context = program_test.start_with_context().await;
+    let write_off_ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: lp_vault::accounts::WriteOffBadDebt {
+            vault: fixture.vault,
+            authority: admin.pubkey(),
+        }
+        .to_account_metas(None),
+        data: lp_vault::instruction::WriteOffBadDebt {
+            financing_amount: 1_000,
+            bad_debt: 400,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[fund_insurance_ix(&fixture, admin.pubkey(), 300), write_off_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.expect("fund then write off");
+
+    // The 300 USDC buffer covers most of the 400 loss; LPs only absorb the last 100
+    let vault_state = fetch_vault_state(&mut context, fixture.vault).await;
+    assert_eq!(vault_state.insurance_balance, 0);
+    assert_eq!(vault_state.vault_usdc_balance, 1_900);
+    assert_eq!(vault_state.locked_for_financing, 0);
+
+    let vault_usdc = context
+        .banks_client
+        .get_account(fixture.vault_usdc_account)
+        .await
+        .unwrap()
+        .expect("vault usdc account");
+    assert_eq!(TokenAccount::unpack(&vault_usdc.data).unwrap().amount, 2_300);
+}
+
+fn add_lp_transfer_accounts(
+    program_test: &mut ProgramTest,
+    admin: solana_program::pubkey::Pubkey,
+    lp_freeze_authority: COption<solana_program::pubkey::Pubkey>,
+) -> (
+    solana_program::pubkey::Pubkey,
+    solana_program::pubkey::Pubkey,
+    solana_program::pubkey::Pubkey,
+) {
+    let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let lp_token_mint = solana_program::pubkey::Pubkey::new_unique();
+    let lp_token_account = solana_program::pubkey::Pubkey::new_unique();
+
+    add_spl_token_program(program_test);
+    program_test.add_account(
+        vault_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                total_shares: 1_000,
+                vault_usdc_balance: 1_000,
+                locked_for_financing: 0,
+                utilization: 0,
+                authority: admin,
+                paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mint = spl_token::state::Mint {
+        mint_authority: COption::Some(vault_pda),
+        supply: 1_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: lp_freeze_authority,
+    };
+    let mut mint_bytes = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(mint, &mut mint_bytes).expect("pack mint");
+    program_test.add_account(
+        lp_token_mint,
+        Account {
+            lamports: 1_000_000,
+            data: mint_bytes,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        lp_token_account,
+        Account {
+            lamports: 1_000_000,
+            data: token_account_data(lp_token_mint, solana_program::pubkey::Pubkey::new_unique(), 1_000),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    (vault_pda, lp_token_mint, lp_token_account)
+}
+
+fn lp_transfer_control_ix(
+    vault: solana_program::pubkey::Pubkey,
+    lp_token_mint: solana_program::pubkey::Pubkey,
+    lp_token_account: solana_program::pubkey::Pubkey,
+    authority: solana_program::pubkey::Pubkey,
+    data: Vec<u8>,
+) -> Instruction {
+    let accounts = lp_vault::accounts::LpTransferControl {
+        vault,
+        lp_token_mint,
+        authority,
+        token_program: spl_token::id(),
+    };
+    let mut metas = accounts.to_account_metas(None);
+    metas.push(solana_sdk::instruction::AccountMeta::new(lp_token_account, false));
+    Instruction {
+        program_id: lp_vault::id(),
+        accounts: metas,
+        data,
+    }
+}
+
+fn assert_vault_error(err: BanksClientError, expected: VaultError) {
+    let expected = u32::from(expected);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, expected, "unexpected error code");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_lp_transfer_freeze_requires_authority() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+
+    let admin = Keypair::new();
+    let attacker = Keypair::new();
+    let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let (_, lp_token_mint, lp_token_account) =
+        add_lp_transfer_accounts(&mut program_test, admin.pubkey(), COption::Some(vault_pda));
+
+    let context = program_test.start_with_context().await;
+
+    for data in [
+        lp_vault::instruction::FreezeLpTransfers {}.data(),
+        lp_vault::instruction::ThawLpTransfers { lift_restriction: true }.data(),
+    ] {
+        let ix = lp_transfer_control_ix(vault_pda, lp_token_mint, lp_token_account, attacker.pubkey(), data);
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &attacker],
+            context.last_blockhash,
+        );
+        let err = context
+            .banks_client
+            .process_transaction(tx)
+            .await
+            .expect_err("non-admin should not toggle LP transfers");
+        assert_vault_error(err, VaultError::Unauthorized);
+    }
+}
+
+#[tokio::test]
+async fn test_lp_transfer_freeze_requires_vault_freeze_authority() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+
+    let admin = Keypair::new();
+    let (vault_pda, lp_token_mint, lp_token_account) =
+        add_lp_transfer_accounts(&mut program_test, admin.pubkey(), COption::Some(admin.pubkey()));
+
+    let mut context = program_test.start_with_context().await;
+    let ix = lp_transfer_control_ix(
+        vault_pda,
+        lp_token_mint,
+        lp_token_account,
+        admin.pubkey(),
+        lp_vault::instruction::FreezeLpTransfers {}.data(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("freeze should require vault freeze authority");
+    assert_vault_error(err, VaultError::MissingFreezeAuthority);
+
+    let vault_state = fetch_vault_state(&mut context, vault_pda).await;
+    assert!(!vault_state.transfers_paused);
+}
+
+/// Vault with 1_000 shares backed by 1_000 USDC, `locked` of which is lent out,
+/// and a user LP account holding `user_shares`.
+fn add_withdraw_all_accounts(
+    program_test: &mut ProgramTest,
+    user: solana_program::pubkey::Pubkey,
+    user_shares: u64,
+    locked: u64,
+) -> lp_vault::accounts::WithdrawUsdc {
+    let usdc_mint = solana_program::pubkey::Pubkey::new_unique();
+    let lp_mint = solana_program::pubkey::Pubkey::new_unique();
+    let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let user_usdc_account = solana_program::pubkey::Pubkey::new_unique();
+    let vault_usdc_account = solana_program::pubkey::Pubkey::new_unique();
+    let user_lp_account = solana_program::pubkey::Pubkey::new_unique();
+
+    add_spl_token_program(program_test);
+    program_test.add_account(
+        vault_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                total_shares: 1_000,
+                vault_usdc_balance: 1_000,
+                locked_for_financing: locked,
+                utilization: 0,
+                authority: Keypair::new().pubkey(),
+                paused: false,
+                transfers_paused: false,
+                min_deposit: 0,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    for (address, data) in [
+        (usdc_mint, mint_data(user)),
+        (lp_mint, mint_data(vault_pda)),
+        (user_usdc_account, token_account_data(usdc_mint, user, 0)),
+        (vault_usdc_account, token_account_data(usdc_mint, vault_pda, 1_000)),
+        (user_lp_account, token_account_data(lp_mint, user, user_shares)),
+    ] {
+        program_test.add_account(
+            address,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: spl_token::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    lp_vault::accounts::WithdrawUsdc {
+        vault: vault_pda,
+        lp_token_mint: lp_mint,
+        user_lp_token_account: user_lp_account,
+        user_usdc_account,
+        vault_usdc_account,
+        lp_position: add_lp_position(program_test, user, lp_vault::NAV_PRECISION),
+        user,
+        token_program: spl_token::id(),
+    }
+}
+
+async fn submit_withdraw_all(
+    program_test: ProgramTest,
+    user: &Keypair,
+    accounts: lp_vault::accounts::WithdrawUsdc,
+) -> Result<(), BanksClientError> {
+    let context = program_test.start_with_context().await;
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: accounts.to_account_metas(None),
+        data: lp_vault::instruction::WithdrawAll {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await
+}
+
+#[tokio::test]
+async fn test_withdraw_all_rejects_empty_lp_balance() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let user = Keypair::new();
+    let accounts = add_withdraw_all_accounts(&mut program_test, user.pubkey(), 0, 0);
+
+    let err = submit_withdraw_all(program_test, &user, accounts)
+        .await
+        .expect_err("withdraw_all with no shares should fail");
+    assert_vault_error(err, VaultError::ZeroAmount);
+}
+
+#[tokio::test]
+async fn test_withdraw_all_redeems_full_balance_against_liquidity() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let user = Keypair::new();
+    // 600 shares redeem 600 USDC, but only 500 is unlocked.
+    let accounts = add_withdraw_all_accounts(&mut program_test, user.pubkey(), 600, 500);
+
+    let err = submit_withdraw_all(program_test, &user, accounts)
+        .await
+        .expect_err("full balance exceeds available liquidity");
+    assert_vault_error(err, VaultError::InsufficientLiquidity);
+}
+
+#[tokio::test]
+async fn test_withdraw_accrues_yield_before_burning_shares() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let user = Keypair::new();
+    let accounts = add_withdraw_all_accounts(&mut program_test, user.pubkey(), 100, 0);
+    // NAV has doubled since the position was last marked
+    add_lp_position(&mut program_test, user.pubkey(), lp_vault::NAV_PRECISION / 2);
+
+    let context = program_test.start_with_context().await;
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: accounts.to_account_metas(None),
+        data: lp_vault::instruction::WithdrawAll {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.expect("withdraw_all");
+
+    let position_account = context
+        .banks_client
+        .get_account(accounts.lp_position)
+        .await
+        .expect("get lp position")
+        .expect("lp position missing");
+    let lp_position =
+        LpPosition::try_deserialize(&mut position_account.data.as_slice()).expect("deserialize lp position");
+    // The 100 redeemed shares earned half a USDC each before they were burned
+    assert_eq!(lp_position.cumulative_yield_earned, 50);
+    assert_eq!(lp_position.last_nav_per_share, lp_vault::NAV_PRECISION);
+}
+
+#[tokio::test]
+async fn test_withdraw_blocked_when_vault_insolvent() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let user = Keypair::new();
+    let accounts = add_withdraw_all_accounts(&mut program_test, user.pubkey(), 100, 0);
+
+    // Unrecorded bad debt: the vault tracks 1,000 USDC but only 700 is in its token account
+    let usdc_mint = solana_program::pubkey::Pubkey::new_unique();
+    program_test.add_account(
+        accounts.vault_usdc_account,
+        Account {
+            lamports: 1_000_000,
+            data: token_account_data(usdc_mint, accounts.vault, 700),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let err = submit_withdraw_all(program_test, &user, accounts)
+        .await
+        .expect_err("withdrawals from an insolvent vault should fail");
+    assert_vault_error(err, VaultError::VaultInsolvent);
+}
+
+#[tokio::test]
+async fn test_deposit_below_minimum_rejected() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let user = Keypair::new();
+    let withdraw_accounts = add_withdraw_all_accounts(&mut program_test, user.pubkey(), 0, 0);
+    program_test.add_account(
+        withdraw_accounts.vault,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                total_shares: 1_000,
+                vault_usdc_balance: 1_000,
+                locked_for_financing: 0,
+                utilization: 0,
+                authority: Keypair::new().pubkey(),
+                paused: false,
+                transfers_paused: false,
+                min_deposit: lp_vault::DEFAULT_MIN_DEPOSIT,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    let accounts = lp_vault::accounts::DepositUsdc {
+        vault: withdraw_accounts.vault,
+        lp_token_mint: withdraw_accounts.lp_token_mint,
+        user_lp_token_account: withdraw_accounts.user_lp_token_account,
+        user_usdc_account: withdraw_accounts.user_usdc_account,
+        vault_usdc_account: withdraw_accounts.vault_usdc_account,
+        lp_position: withdraw_accounts.lp_position,
+        user: user.pubkey(),
+        token_program: spl_token::id(),
+    };
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: accounts.to_account_metas(None),
+        data: lp_vault::instruction::DepositUsdc {
+            amount: lp_vault::DEFAULT_MIN_DEPOSIT - 1,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &user],
+        context.last_blockhash,
+    );
+
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("deposit below the minimum should fail");
+    assert_vault_error(err, VaultError::DepositTooSmall);
+}
+
+#[tokio::test]
+async fn test_first_deposit_below_seed_rejected() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let user = Keypair::new();
+    let withdraw_accounts = add_withdraw_all_accounts(&mut program_test, user.pubkey(), 0, 0);
+    program_test.add_account(
+        withdraw_accounts.vault,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                // Empty vault: the first deposit sets the base share price
+                total_shares: 0,
+                vault_usdc_balance: 0,
+                locked_for_financing: 0,
+                utilization: 0,
+                authority: Keypair::new().pubkey(),
+                paused: false,
+                transfers_paused: false,
+                min_deposit: lp_vault::DEFAULT_MIN_DEPOSIT,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: lp_vault::DEFAULT_SEED_DEPOSIT,
+                version: lp_vault::STATE_VERSION,
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    let accounts = lp_vault::accounts::DepositUsdc {
+        vault: withdraw_accounts.vault,
+        lp_token_mint: withdraw_accounts.lp_token_mint,
+        user_lp_token_account: withdraw_accounts.user_lp_token_account,
+        user_usdc_account: withdraw_accounts.user_usdc_account,
+        vault_usdc_account: withdraw_accounts.vault_usdc_account,
+        lp_position: withdraw_accounts.lp_position,
+        user: user.pubkey(),
+        token_program: spl_token::id(),
+    };
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: accounts.to_account_metas(None),
+        data: lp_vault::instruction::DepositUsdc {
+            amount: lp_vault::DEFAULT_SEED_DEPOSIT - 1,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &user],
+        context.last_blockhash,
+    );
+
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("first deposit below the seed should fail");
+    assert_vault_error(err, VaultError::InsufficientSeedDeposit);
+}
+
+#[tokio::test]
+async fn test_deposit_rejected_while_lp_transfers_paused() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    let user = Keypair::new();
+    let withdraw_accounts = add_withdraw_all_accounts(&mut program_test, user.pubkey(), 0, 0);
+    program_test.add_account(
+        withdraw_accounts.vault,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                total_shares: 1_000,
+                vault_usdc_balance: 1_000,
+                locked_for_financing: 0,
+                utilization: 0,
+                authority: Keypair::new().pubkey(),
+                paused: false,
+                transfers_paused: true,
+                min_deposit: lp_vault::DEFAULT_MIN_DEPOSIT,
+                high_water_mark: lp_vault::NAV_PRECISION,
+                performance_fee_bps: 0,
+                max_write_off_per_call: 0,
+                max_write_off_per_epoch: 0,
+                write_off_epoch: 0,
+                written_off_this_epoch: 0,
+                insurance_balance: 0,
+                seed_deposit: 0,
+                version: lp_vault::STATE_VERSION,
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    let accounts = lp_vault::accounts::DepositUsdc {
+        vault: withdraw_accounts.vault,
+        lp_token_mint: withdraw_accounts.lp_token_mint,
+        user_lp_token_account: withdraw_accounts.user_lp_token_account,
+        user_usdc_account: withdraw_accounts.user_usdc_account,
+        vault_usdc_account: withdraw_accounts.vault_usdc_account,
+        lp_position: withdraw_accounts.lp_position,
+        user: user.pubkey(),
+        token_program: spl_token::id(),
+    };
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: accounts.to_account_metas(None),
+        data: lp_vault::instruction::DepositUsdc {
+            amount: lp_vault::DEFAULT_MIN_DEPOSIT,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &user],
+        context.last_blockhash,
+    );
+
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("deposit should fail while LP transfers are restricted");
+    assert_vault_error(err, VaultError::LpTransfersPaused);
+}
+
+#[test]
+fn test_performance_fee_respects_high_water_mark() {
+    let mut vault = LPVaultState {
+        total_shares: 1_000_000_000,
+        vault_usdc_balance: 1_000_000_000,
+        locked_for_financing: 0,
+        utilization: 0,
+        authority: solana_program::pubkey::Pubkey::new_unique(),
+        paused: false,
+        transfers_paused: false,
+        min_deposit: 0,
+        high_water_mark: lp_vault::NAV_PRECISION,
+        performance_fee_bps: 1_000,
+        max_write_off_per_call: 0,
+        max_write_off_per_epoch: 0,
+        write_off_epoch: 0,
+        written_off_this_epoch: 0,
+        insurance_balance: 0,
+        seed_deposit: 0,
+        version: lp_vault::STATE_VERSION,
+    };
+
+    // Gain: NAV 1.00 -> 1.10 charges 10% of the 100 USDC above the mark
+    vault.vault_usdc_balance = 1_100_000_000;
+    assert_eq!(vault.accrue_performance_fee().unwrap(), 10_000_000);
+    assert_eq!(vault.vault_usdc_balance, 1_090_000_000);
+    assert_eq!(vault.high_water_mark, 1_090_000);
+
+    // Loss, then a recovery that stays below the previous peak: no fee, mark unchanged
+    vault.vault_usdc_balance = 900_000_000;
+    assert_eq!(vault.accrue_performance_fee().unwrap(), 0);
+    vault.vault_usdc_balance = 1_080_000_000;
+    assert_eq!(vault.accrue_performance_fee().unwrap(), 0);
+    assert_eq!(vault.vault_usdc_balance, 1_080_000_000);
+    assert_eq!(vault.high_water_mark, 1_090_000);
+
+    // New peak: only the 100 USDC above 1.09 is charged
+    vault.vault_usdc_balance = 1_190_000_000;
+    assert_eq!(vault.accrue_performance_fee().unwrap(), 10_000_000);
+    assert_eq!(vault.high_water_mark, 1_180_000);
+}
+
+fn batch_vault_state(total_shares: u64, vault_usdc_balance: u64) -> LPVaultState {
+    LPVaultState {
+        total_shares,
+        vault_usdc_balance,
+        locked_for_financing: 0,
+        utilization: 0,
+        authority: Keypair::new().pubkey(),
+        paused: false,
+        transfers_paused: false,
+        min_deposit: 0,
+        high_water_mark: lp_vault::NAV_PRECISION,
+        performance_fee_bps: 0,
+        max_write_off_per_call: 0,
+        max_write_off_per_epoch: 0,
+        write_off_epoch: 0,
+        written_off_this_epoch: 0,
+        insurance_balance: 0,
+        seed_deposit: 0,
+        version: lp_vault::STATE_VERSION,
+    }
+}
+
+#[tokio::test]
+async fn test_batch_deposit_mints_shares_per_lp() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    add_spl_token_program(&mut program_test);
+
+    let depositor = Keypair::new();
+    let usdc_mint = solana_program::pubkey::Pubkey::new_unique();
+    let lp_mint = solana_program::pubkey::Pubkey::new_unique();
+    let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let vault_usdc_account = solana_program::pubkey::Pubkey::new_unique();
+
+    program_test.add_account(
+        vault_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&batch_vault_state(0, 0)),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    for (key, data) in [
+        (usdc_mint, mint_data(depositor.pubkey())),
+        (lp_mint, mint_data(vault_pda)),
+        (vault_usdc_account, token_account_data(usdc_mint, vault_pda, 0)),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: spl_token::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // The fund holds the USDC; each LP receives shares in their own LP token account
+    let amounts = vec![1_000u64, 2_000, 3_000];
+    let mut remaining_accounts = Vec::new();
+    let mut lp_accounts = Vec::new();
+    for _ in &amounts {
+        let lp = Keypair::new();
+        let source_usdc = solana_program::pubkey::Pubkey::new_unique();
+        let lp_account = solana_program::pubkey::Pubkey::new_unique();
+        for (key, data) in [
+            (source_usdc, token_account_data(usdc_mint, depositor.pubkey(), 5_000)),
+            (lp_account, token_account_data(lp_mint, lp.pubkey(), 0)),
+        ] {
+            program_test.add_account(
+                key,
+                Account {
+                    lamports: 1_000_000,
+                    data,
+                    owner: spl_token::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            );
+        }
+        let lp_position = add_lp_position(&mut program_test, lp.pubkey(), lp_vault::NAV_PRECISION);
+        remaining_accounts.push(solana_program::instruction::AccountMeta::new(source_usdc, false));
+        remaining_accounts.push(solana_program::instruction::AccountMeta::new(lp_account, false));
+        remaining_accounts.push(solana_program::instruction::AccountMeta::new(lp_position, false));
+        lp_accounts.push(lp_account);
+    }
+
+    let mut context = program_test.start_with_context().await;
+    let mut metas = lp_vault::accounts::BatchDeposit {
+        vault: vault_pda,
+        lp_token_mint: lp_mint,
+        vault_usdc_account,
+        depositor: depositor.pubkey(),
+        token_program: spl_token::id(),
+    }
+    .to_account_metas(None);
+    metas.extend(remaining_accounts);
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: metas,
+        data: lp_vault::instruction::BatchDeposit {
+            amounts: amounts.clone(),
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &depositor],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let vault_state = fetch_vault_state(&mut context, vault_pda).await;
+    assert_eq!(vault_state.total_shares, 6_000);
+    assert_eq!(vault_state.vault_usdc_balance, 6_000);
+
+    for (lp_account, amount) in lp_accounts.iter().zip(amounts) {
+        let account = context
+            .banks_client
+            .get_account(*lp_account)
+            .await
+            .expect("get lp account")
+            .expect("lp account missing");
+        let lp_state = TokenAccount::unpack(&account.data).expect("unpack lp account");
+        // Share price stays at 1:1, so each LP is minted exactly its deposit
+        assert_eq!(lp_state.amount, amount);
+    }
+}
+
+#[tokio::test]
+async fn test_batch_deposit_accrues_existing_lp_position() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+    add_spl_token_program(&mut program_test);
+
+    let depositor = Keypair::new();
+    let lp = Keypair::new();
+    let usdc_mint = solana_program::pubkey::Pubkey::new_unique();
+    let lp_mint = solana_program::pubkey::Pubkey::new_unique();
+    let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let vault_usdc_account = solana_program::pubkey::Pubkey::new_unique();
+    let source_usdc = solana_program::pubkey::Pubkey::new_unique();
+    let lp_account = solana_program::pubkey::Pubkey::new_unique();
+
+    // The LP holds 1_000 shares marked at NAV 1.0; the vault has since grown to NAV 1.1
+    let vault_state = batch_vault_state(1_000, 1_100);
+    program_test.add_account(
+        vault_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&vault_state),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    for (key, data) in [
+        (usdc_mint, mint_data(depositor.pubkey())),
+        (lp_mint, mint_data(vault_pda)),
+        (vault_usdc_account, token_account_data(usdc_mint, vault_pda, 1_100)),
+        (source_usdc, token_account_data(usdc_mint, depositor.pubkey(), 1_100)),
+        (lp_account, token_account_data(lp_mint, lp.pubkey(), 1_000)),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: spl_token::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+    let lp_position = add_lp_position(&mut program_test, lp.pubkey(), lp_vault::NAV_PRECISION);
+
+    let mut context = program_test.start_with_context().await;
+    let mut metas = lp_vault::accounts::BatchDeposit {
+        vault: vault_pda,
+        lp_token_mint: lp_mint,
+        vault_usdc_account,
+        depositor: depositor.pubkey(),
+        token_program: spl_token::id(),
+    }
+    .to_account_metas(None);
+    metas.extend([
+        solana_program::instruction::AccountMeta::new(source_usdc, false),
+        solana_program::instruction::AccountMeta::new(lp_account, false),
+        solana_program::instruction::AccountMeta::new(lp_position, false),
+    ]);
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: metas,
+        data: lp_vault::instruction::BatchDeposit { amounts: vec![1_100] }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &depositor],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // Distribute 200 USDC of yield across the 2_000 shares: NAV 1.1 -> 1.2
+    let vault_state = fetch_vault_state(&mut context, vault_pda).await;
+    assert_eq!(vault_state.total_shares, 2_000);
+    context.set_account(
+        &vault_pda,
+        &Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&LPVaultState {
+                vault_usdc_balance: vault_state.vault_usdc_balance + 200,
+                ..vault_state
+            }),
+            owner: lp_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+
+    let ix = Instruction {
+        program_id: lp_vault::id(),
+        accounts: lp_vault::accounts::ReportLpYield {
+            vault: vault_pda,
+            lp_position,
+            lp_token_mint: lp_mint,
+            lp_token_account: lp_account,
+        }
+        .to_account_metas(None),
+        data: lp_vault::instruction::ReportLpYield {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let position_account = context
+        .banks_client
+        .get_account(lp_position)
+        .await
+        .expect("get lp position")
+        .expect("lp position missing");
+    let position =
+        LpPosition::try_deserialize(&mut position_account.data.as_slice()).expect("deserialize lp position");
+    // 100 on the original 1_000 shares before the deposit, then 200 on all 2_000 shares;
+    // skipping the accrual would credit the 1.0 -> 1.2 move on every share (400)
+    assert_eq!(position.cumulative_yield_earned, 300);
+    assert_eq!(position.last_nav_per_share, lp_vault::NAV_PRECISION * 12 / 10);
+}
+
+#[test]
+fn test_lp_yield_tracker_accumulates_across_distributions() {
+    let mut vault = LPVaultState {
+        total_shares: 1_000_000_000,
+        vault_usdc_balance: 1_000_000_000,
+        locked_for_financing: 0,
+        utilization: 0,
+        authority: solana_program::pubkey::Pubkey::new_unique(),
+        paused: false,
+        transfers_paused: false,
+        min_deposit: 0,
+        high_water_mark: lp_vault::NAV_PRECISION,
+        performance_fee_bps: 0,
+        max_write_off_per_call: 0,
+        max_write_off_per_epoch: 0,
+        write_off_epoch: 0,
+        written_off_this_epoch: 0,
+        insurance_balance: 0,
+        seed_deposit: 0,
+        version: lp_vault::STATE_VERSION,
+    };
+    let mut lp_position = LpPosition {
+        owner: solana_program::pubkey::Pubkey::new_unique(),
+        last_nav_per_share: vault.nav_per_share().unwrap(),
+        cumulative_yield_earned: 0,
+    };
+    // The LP holds a quarter of the vault.
+    let shares = 250_000_000;
+
+    // First distribution: +40 USDC to the vault, 10 USDC of it on the LP's shares
+    vault.vault_usdc_balance += 40_000_000;
+    assert_eq!(lp_position.accrue_yield(shares, vault.nav_per_share().unwrap()).unwrap(), 10_000_000);
+
+    // Second distribution: +80 USDC, another 20 USDC
+    vault.vault_usdc_balance += 80_000_000;
+    assert_eq!(lp_position.accrue_yield(shares, vault.nav_per_share().unwrap()).unwrap(), 20_000_000);
+    assert_eq!(lp_position.cumulative_yield_earned, 30_000_000);
+
+    // Reporting again without a distribution credits nothing
+    assert_eq!(lp_position.accrue_yield(shares, vault.nav_per_share().unwrap()).unwrap(), 0);
+
+    // A bad-debt drawdown and its recovery are not counted as yield; only new highs are
+    vault.vault_usdc_balance -= 100_000_000;
+    assert_eq!(lp_position.accrue_yield(shares, vault.nav_per_share().unwrap()).unwrap(), 0);
+    vault.vault_usdc_balance += 140_000_000;
+    assert_eq!(lp_position.accrue_yield(shares, vault.nav_per_share().unwrap()).unwrap(), 10_000_000);
+    assert_eq!(lp_position.cumulative_yield_earned, 40_000_000);
+}
+
+#[tokio::test]
+async fn test_migrate_vault_from_pre_version_layout() {
+    let mut program_test =
+        ProgramTest::new("lp_vault", lp_vault::id(), solana_program_test::processor!(lp_vault_processor));
+
+    let admin = Keypair::new();
+    let attacker = Keypair::new();
+    let (vault_pda, _) = solana_program::pubkey::Pubkey::find_program_address(&[b"vault"], &lp_vault::id());
+    let space = 8 + LPVaultState::LEN;
+    add_baseline_layout_account::<LPVaultState, _>(
+        &mut program_test,
+        vault_pda,
+        &baseline::LPVaultState {
+            total_shares: 5_000_000,
+            vault_usdc_balance: 7_000_000,
+            locked_for_financing: 1_000_000,
+            utilization: 1_428,
+            authority: admin.pubkey(),
+            paused: false,
+        },
+        8 + baseline::LPVaultState::LEN,
+        space,
+        lp_vault::id(),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let migrate_ix = |authority: solana_program::pubkey::Pubkey| Instruction {
+        program_id: lp_vault::id(),
+        accounts: lp_vault::accounts::MigrateVaultVersion {
+            vault: vault_pda,
+            authority,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: lp_vault::instruction::MigrateVaultVersion {}.data(),
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_ix(attacker.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &attacker],
+        context.last_blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("non-admin should not migrate the vault");
+    assert_vault_error(err, VaultError::Unauthorized);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_ix(admin.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("admin migrates the vault");
+
+    let account = context
+        .banks_client
+        .get_account(vault_pda)
+        .await
+        .expect("get vault account")
+        .expect("vault account missing");
+    assert_eq!(account.data.len(), space);
+    let vault = fetch_vault_state(&mut context, vault_pda).await;
+    assert_eq!(vault.version, lp_vault::STATE_VERSION);
+    assert_eq!(vault.authority, admin.pubkey());
+    assert_eq!(vault.total_shares, 5_000_000);
+    assert_eq!(vault.vault_usdc_balance, 7_000_000);
+    assert_eq!(vault.locked_for_financing, 1_000_000);
+    // Fields added since the baseline get initialize_vault's defaults, except the
+    // high-water mark, which starts at the NAV the vault had already reached
+    assert_eq!(vault.min_deposit, lp_vault::DEFAULT_MIN_DEPOSIT);
+    assert_eq!(vault.seed_deposit, lp_vault::DEFAULT_SEED_DEPOSIT);
+    assert_eq!(vault.high_water_mark, 1_400_000);
+    assert_eq!(vault.performance_fee_bps, 0);
+    assert!(!vault.transfers_paused);
+}
@@ -1,9 +1,12 @@
 mod common;
 
-use anchor_lang::prelude::{AccountDeserialize, AccountSerialize, Pubkey};
+use anchor_lang::prelude::{AccountDeserialize, AccountSerialize, Clock, Pubkey};
 use anchor_lang::InstructionData;
 use anchor_lang::ToAccountMetas;
-use oracle_framework::{OracleError, OracleSource, OracleState};
+use common::setup::{add_baseline_layout_account, baseline};
+use oracle_framework::{
+    median_price, OracleError, OracleSource, OracleState, MAX_STALENESS_SLOTS,
+};
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program_test::{BanksClientError, ProgramTest};
@@ -125,6 +128,10 @@ async fn test_update_price_authorization() {
             frozen_slot: 0,
             last_update_slot: 0,
             paused: false,
+            min_update_interval_slots: 0,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: oracle_framework::STATE_VERSION,
         },
     );
 
@@ -162,6 +169,103 @@ async fn test_update_price_authorization() {
     }
 }
 
+#[tokio::test]
+async fn test_update_price_respects_min_update_interval() {
+    let mut program_test = ProgramTest::new(
+        "oracle_framework",
+        oracle_framework::id(),
+        solana_program_test::processor!(oracle_framework_processor),
+    );
+
+    let admin = Keypair::new();
+    let oracle_pda = Pubkey::find_program_address(&[b"oracle"], &oracle_framework::id()).0;
+    let interval = 10;
+    add_oracle_account(
+        &mut program_test,
+        oracle_pda,
+        OracleState {
+            authority: admin.pubkey(),
+            protocol_admin: admin.pubkey(),
+            pyth_price: 1,
+            switchboard_price: 1,
+            synthetic_twap: 1,
+            last_twap_window: 0,
+            frozen_price: 0,
+            frozen_slot: 0,
+            last_update_slot: 0,
+            paused: false,
+            min_update_interval_slots: interval,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: oracle_framework::STATE_VERSION,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin.pubkey()).await;
+
+    // Distinct prices keep each transaction's signature unique
+    let update_tx = |source: OracleSource, price: i64, blockhash| {
+        let ix = Instruction {
+            program_id: oracle_framework::id(),
+            accounts: oracle_framework::accounts::OracleCtx {
+                oracle: oracle_pda,
+                authority: admin.pubkey(),
+            }
+            .to_account_metas(None),
+            data: oracle_framework::instruction::UpdateOraclePrice { source, price }.data(),
+        };
+        Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash)
+    };
+
+    // The first update is always accepted
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    context
+        .banks_client
+        .process_transaction(update_tx(OracleSource::Pyth, 5, blockhash))
+        .await
+        .expect("first update is accepted");
+    let account = context.banks_client.get_account(oracle_pda).await.unwrap().unwrap();
+    let first_update_slot = OracleState::try_deserialize(&mut account.data.as_slice())
+        .unwrap()
+        .pyth_update_slot;
+
+    // The interval is per feed, so switchboard can still report in the same slot
+    context
+        .banks_client
+        .process_transaction(update_tx(OracleSource::Switchboard, 5, blockhash))
+        .await
+        .expect("switchboard update is not blocked by pyth");
+
+    // A second pyth update inside the interval is refused
+    let err = context
+        .banks_client
+        .process_transaction(update_tx(OracleSource::Pyth, 6, blockhash))
+        .await
+        .expect_err("update inside the interval should fail");
+    let expected = u32::from(OracleError::UpdateTooFrequent);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, expected, "unexpected error code");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // Once the interval has elapsed the feed accepts updates again
+    context.warp_to_slot(first_update_slot + interval).unwrap();
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    context
+        .banks_client
+        .process_transaction(update_tx(OracleSource::Pyth, 7, blockhash))
+        .await
+        .expect("update after the interval is accepted");
+    let account = context.banks_client.get_account(oracle_pda).await.unwrap().unwrap();
+    let state = OracleState::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(state.pyth_price, 7);
+    assert_eq!(state.switchboard_price, 5);
+    assert!(state.pyth_update_slot >= first_update_slot + interval);
+}
+
 #[tokio::test]
 async fn test_price_bounds_validation() {
     let mut program_test = ProgramTest::new(
@@ -186,6 +290,10 @@ async fn test_price_bounds_validation() {
             frozen_slot: 0,
             last_update_slot: 0,
             paused: false,
+            min_update_interval_slots: 0,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: oracle_framework::STATE_VERSION,
         },
     );
 
@@ -248,6 +356,10 @@ async fn test_staleness_detection() {
             frozen_slot: 0,
             last_update_slot: 0,
             paused: false,
+            min_update_interval_slots: 0,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: oracle_framework::STATE_VERSION,
         },
     );
 
@@ -282,6 +394,140 @@ async fn test_staleness_detection() {
     }
 }
 
+#[tokio::test]
+async fn test_consistency_check_catches_single_stale_feed() {
+    let mut program_test = ProgramTest::new(
+        "oracle_framework",
+        oracle_framework::id(),
+        solana_program_test::processor!(oracle_framework_processor),
+    );
+
+    let admin = Keypair::new();
+    let oracle_pda = Pubkey::find_program_address(&[b"oracle"], &oracle_framework::id()).0;
+    // Pyth updated recently, switchboard long ago: the oracle-wide slot looks fresh
+    let oracle = OracleState {
+        authority: admin.pubkey(),
+        protocol_admin: admin.pubkey(),
+        pyth_price: 100,
+        switchboard_price: 100,
+        synthetic_twap: 100,
+        last_twap_window: 0,
+        frozen_price: 0,
+        frozen_slot: 0,
+        last_update_slot: 250,
+        paused: false,
+        min_update_interval_slots: 0,
+        pyth_update_slot: 250,
+        switchboard_update_slot: 10,
+        version: oracle_framework::STATE_VERSION,
+    };
+    assert!(!oracle.feeds_fresh(260, 50));
+    add_oracle_account(&mut program_test, oracle_pda, oracle);
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin.pubkey()).await;
+    context.warp_to_slot(260).unwrap();
+
+    let accounts = oracle_framework::accounts::OracleCtx {
+        oracle: oracle_pda,
+        authority: admin.pubkey(),
+    };
+    let ix = Instruction {
+        program_id: oracle_framework::id(),
+        accounts: accounts.to_account_metas(None),
+        data: oracle_framework::instruction::ValidateOracleConsistency {
+            tolerance_bps: 100,
+            max_staleness_slots: 50,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+
+    let result = context.banks_client.process_transaction(tx).await;
+    let err = result.expect_err("stale switchboard feed should fail");
+    let expected = u32::from(OracleError::StalePrice);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, expected, "unexpected error code");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_report_oracle_freshness_flags_stale_feed() {
+    let mut program_test = ProgramTest::new(
+        "oracle_framework",
+        oracle_framework::id(),
+        solana_program_test::processor!(oracle_framework_processor),
+    );
+
+    let admin = Keypair::new();
+    let oracle_pda = Pubkey::find_program_address(&[b"oracle"], &oracle_framework::id()).0;
+    add_oracle_account(
+        &mut program_test,
+        oracle_pda,
+        OracleState {
+            authority: admin.pubkey(),
+            protocol_admin: admin.pubkey(),
+            pyth_price: 1,
+            switchboard_price: 1,
+            synthetic_twap: 1,
+            last_twap_window: 0,
+            frozen_price: 0,
+            frozen_slot: 0,
+            last_update_slot: 0,
+            paused: false,
+            min_update_interval_slots: 0,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: oracle_framework::STATE_VERSION,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let ix = Instruction {
+        program_id: oracle_framework::id(),
+        accounts: oracle_framework::accounts::ReportOracleFreshness { oracle: oracle_pda }
+            .to_account_metas(None),
+        data: oracle_framework::instruction::ReportOracleFreshness {}.data(),
+    };
+
+    // Report once while fresh and once after warping past the staleness window; the
+    // emitted flag is OracleState::is_stale at the bank's current slot
+    let mut stale_flags = Vec::new();
+    for warp_slot in [None, Some(MAX_STALENESS_SLOTS + 50)] {
+        if let Some(slot) = warp_slot {
+            context.warp_to_slot(slot).unwrap();
+        }
+        let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[ix.clone()],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(tx)
+            .await
+            .expect("freshness report is permissionless");
+
+        let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+        let account = context.banks_client.get_account(oracle_pda).await.unwrap().unwrap();
+        let state = OracleState::try_deserialize(&mut account.data.as_slice()).unwrap();
+        // The heartbeat is read-only
+        assert_eq!(state.last_update_slot, 0);
+        stale_flags.push(state.is_stale(clock.slot));
+    }
+    assert_eq!(stale_flags, vec![false, true]);
+}
+
 #[tokio::test]
 async fn test_calculate_twap_authorization() {
     let mut program_test = ProgramTest::new(
@@ -307,6 +553,10 @@ async fn test_calculate_twap_authorization() {
             frozen_slot: 0,
             last_update_slot: 0,
             paused: false,
+            min_update_interval_slots: 0,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: oracle_framework::STATE_VERSION,
         },
     );
 
@@ -365,6 +615,10 @@ async fn test_freeze_snapshot_authorization() {
             frozen_slot: 0,
             last_update_slot: 0,
             paused: false,
+            min_update_interval_slots: 0,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: oracle_framework::STATE_VERSION,
         },
     );
 
@@ -422,6 +676,10 @@ async fn test_pause_oracle_updates() {
             frozen_slot: 0,
             last_update_slot: 0,
             paused: true,
+            min_update_interval_slots: 0,
+            pyth_update_slot: 0,
+            switchboard_update_slot: 0,
+            version: oracle_framework::STATE_VERSION,
         },
     );
 
@@ -458,3 +716,130 @@ async fn test_pause_oracle_updates() {
         other => panic!("unexpected error: {other:?}"),
     }
 }
+
+#[tokio::test]
+async fn test_migrate_oracle_from_pre_version_layout() {
+    let mut program_test = ProgramTest::new(
+        "oracle_framework",
+        oracle_framework::id(),
+        solana_program_test::processor!(oracle_framework_processor),
+    );
+
+    let admin = Keypair::new();
+    let attacker = Keypair::new();
+    let oracle_pda = Pubkey::find_program_address(&[b"oracle"], &oracle_framework::id()).0;
+    let space = 8 + OracleState::LEN;
+    add_baseline_layout_account::<OracleState, _>(
+        &mut program_test,
+        oracle_pda,
+        &baseline::OracleState {
+            authority: admin.pubkey(),
+            protocol_admin: admin.pubkey(),
+            pyth_price: 100_000_000,
+            switchboard_price: 101_000_000,
+            synthetic_twap: 99_000_000,
+            last_twap_window: 0,
+            frozen_price: 0,
+            frozen_slot: 0,
+            last_update_slot: 42,
+            paused: false,
+        },
+        8 + baseline::OracleState::LEN,
+        space,
+        oracle_framework::id(),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let migrate_ix = |protocol_admin: Pubkey| Instruction {
+        program_id: oracle_framework::id(),
+        accounts: oracle_framework::accounts::MigrateOracleVersion {
+            oracle: oracle_pda,
+            protocol_admin,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: oracle_framework::instruction::MigrateOracleVersion {}.data(),
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_ix(attacker.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &attacker],
+        context.last_blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("non-admin should not migrate the oracle");
+    let expected = u32::from(OracleError::Unauthorized);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, expected, "unexpected error code");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_ix(admin.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("admin migrates the oracle");
+
+    let account = context
+        .banks_client
+        .get_account(oracle_pda)
+        .await
+        .unwrap()
+        .expect("oracle account");
+    assert_eq!(account.data.len(), space);
+    let oracle = OracleState::try_deserialize(&mut account.data.as_slice()).expect("deserialize oracle");
+    assert_eq!(oracle.version, oracle_framework::STATE_VERSION);
+    assert_eq!(oracle.protocol_admin, admin.pubkey());
+    assert_eq!(oracle.switchboard_price, 101_000_000);
+    assert_eq!(oracle.last_update_slot, 42);
+    // No per-feed slot existed, so both feeds read as stale until pushed again
+    assert_eq!(oracle.min_update_interval_slots, 0);
+    assert_eq!(oracle.pyth_update_slot, 0);
+    assert_eq!(oracle.switchboard_update_slot, 0);
+}
+
+#[test]
+fn test_median_price_ignores_single_outlier_feed() {
+    // Switchboard reports a manipulated price; the median stays with the honest feeds
+    assert_eq!(median_price(&[100_000_000, 500_000_000, 101_000_000]), Some(101_000_000));
+    assert_eq!(median_price(&[1, 100_000_000, 101_000_000]), Some(100_000_000));
+
+    let oracle = OracleState {
+        authority: Pubkey::new_unique(),
+        protocol_admin: Pubkey::new_unique(),
+        pyth_price: 100_000_000,
+        switchboard_price: 10_000_000_000,
+        synthetic_twap: 99_000_000,
+        last_twap_window: 0,
+        frozen_price: 0,
+        frozen_slot: 0,
+        last_update_slot: 0,
+        paused: false,
+        min_update_interval_slots: 0,
+        pyth_update_slot: 0,
+        switchboard_update_slot: 0,
+        version: oracle_framework::STATE_VERSION,
+    };
+    assert_eq!(oracle.median_price(), Some(100_000_000));
+}
+
+#[test]
+fn test_median_price_skips_unset_feeds() {
+    assert_eq!(median_price(&[0, 0, 0]), None);
+    assert_eq!(median_price(&[0, 100_000_000, 0]), Some(100_000_000));
+    // Two live feeds: midpoint of the pair
+    assert_eq!(median_price(&[100_000_000, 0, 102_000_000]), Some(101_000_000));
+    assert_eq!(median_price(&[i64::MAX, i64::MAX]), Some(i64::MAX));
+}
@@ -1,16 +1,22 @@
+mod common;
+
 use anchor_lang::prelude::{AccountDeserialize, AccountSerialize, Pubkey};
 use anchor_lang::InstructionData;
 use anchor_lang::ToAccountMetas;
+use anchor_spl::token::spl_token;
+use common::setup::{add_baseline_layout_account, add_lp_vault_program, add_mint_account, add_token_account, baseline};
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
+use solana_program_pack::Pack;
 use solana_program_test::{BanksClient, BanksClientError, ProgramTest};
 use solana_sdk::account::Account;
 use solana_sdk::instruction::{Instruction, InstructionError};
 use solana_sdk::signature::{Keypair, Signer};
 use solana_sdk::system_instruction;
+use solana_sdk::system_program;
 use solana_sdk::transaction::Transaction;
 use solana_sdk::transaction::TransactionError;
-use treasury_engine::{Treasury, TreasuryError};
+use treasury_engine::{CompoundDestination, Treasury, TreasuryError};
 
 fn serialize_anchor_account<T: AccountSerialize>(data: &T) -> Vec<u8> {
     let mut buf = Vec::new();
@@ -80,6 +86,14 @@ async fn test_allocate_requires_admin_and_updates() {
                 carry_accrued: 0,
                 compounded_xrs: 0,
                 paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Xrs,
+                reserve_balance: 0,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
             }),
             owner: treasury_engine::id(),
             executable: false,
@@ -176,6 +190,14 @@ async fn test_co_financing_limits_enforced() {
                 carry_accrued: 0,
                 compounded_xrs: 0,
                 paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Xrs,
+                reserve_balance: 0,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
             }),
             owner: treasury_engine::id(),
             executable: false,
@@ -222,6 +244,121 @@ async fn test_co_financing_limits_enforced() {
     assert_eq!(treasury.co_financing_outstanding, 400);
 }
 
+#[tokio::test]
+async fn test_recall_returns_outstanding_to_baseline() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let (treasury_pda, _) = Pubkey::find_program_address(&[b"treasury"], &treasury_engine::id());
+
+    program_test.add_account(
+        treasury_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&Treasury {
+                admin: admin.pubkey(),
+                lp_contributed: 1_000,
+                co_financing_outstanding: 100,
+                base_fee_accrued: 0,
+                carry_accrued: 0,
+                compounded_xrs: 0,
+                paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Xrs,
+                reserve_balance: 0,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
+            }),
+            owner: treasury_engine::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin).await;
+
+    let accounts = treasury_engine::accounts::TreasuryCtx {
+        treasury: treasury_pda,
+        authority: admin.pubkey(),
+    };
+    let allocate_ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: accounts.to_account_metas(None),
+        data: treasury_engine::instruction::TreasuryAllocate {
+            co_finance_amount: 300,
+        }
+        .data(),
+    };
+    let recall_ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: accounts.to_account_metas(None),
+        data: treasury_engine::instruction::TreasuryRecall { amount: 300 }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[allocate_ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("allocate should succeed");
+    let treasury = fetch_treasury(&mut context.banks_client, treasury_pda).await;
+    assert_eq!(treasury.co_financing_outstanding, 400);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[recall_ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("recall should succeed");
+    let treasury = fetch_treasury(&mut context.banks_client, treasury_pda).await;
+    assert_eq!(treasury.co_financing_outstanding, 100);
+
+    // Recalling more than is outstanding must not underflow.
+    let ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: accounts.to_account_metas(None),
+        data: treasury_engine::instruction::TreasuryRecall { amount: 101 }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(tx).await;
+    let err = result.expect_err("over-recall should fail");
+    let expected = u32::from(TreasuryError::RecallExceedsOutstanding);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => {
+            assert_eq!(code, expected, "unexpected error code");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    let treasury = fetch_treasury(&mut context.banks_client, treasury_pda).await;
+    assert_eq!(treasury.co_financing_outstanding, 100);
+}
+
 #[tokio::test]
 async fn test_compound_resets_yield_balances() {
     let mut program_test = ProgramTest::new(
@@ -245,6 +382,14 @@ async fn test_compound_resets_yield_balances() {
                 carry_accrued: 50,
                 compounded_xrs: 1_000,
                 paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Xrs,
+                reserve_balance: 0,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
             }),
             owner: treasury_engine::id(),
             executable: false,
@@ -282,6 +427,217 @@ async fn test_compound_resets_yield_balances() {
     assert_eq!(treasury.carry_accrued, 0);
 }
 
+#[tokio::test]
+async fn test_compound_respects_min_interval() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let (treasury_pda, _) = Pubkey::find_program_address(&[b"treasury"], &treasury_engine::id());
+
+    program_test.add_account(
+        treasury_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&Treasury {
+                admin: admin.pubkey(),
+                lp_contributed: 0,
+                co_financing_outstanding: 0,
+                base_fee_accrued: 100,
+                carry_accrued: 100,
+                compounded_xrs: 0,
+                paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Xrs,
+                reserve_balance: 0,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
+            }),
+            owner: treasury_engine::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin).await;
+
+    let schedule_accounts = treasury_engine::accounts::AdminTreasuryAction {
+        treasury: treasury_pda,
+        admin_authority: admin.pubkey(),
+    };
+    let compound_accounts = treasury_engine::accounts::TreasuryCtx {
+        treasury: treasury_pda,
+        authority: admin.pubkey(),
+    };
+    let compound_ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: compound_accounts.to_account_metas(None),
+        data: treasury_engine::instruction::TreasuryCompoundXrs {}.data(),
+    };
+
+    let schedule_ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: schedule_accounts.to_account_metas(None),
+        data: treasury_engine::instruction::SetCompoundSchedule {
+            min_compound_interval_slots: 100,
+            compound_rate_bps: 5_000,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[schedule_ix, compound_ix.clone()],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("first compound should succeed");
+
+    let treasury = fetch_treasury(&mut context.banks_client, treasury_pda).await;
+    assert_eq!(treasury.compounded_xrs, 100);
+    let compounded_at = treasury.last_compound_slot;
+
+    // Within the interval: rejected.
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[compound_ix.clone()],
+        Some(&admin.pubkey()),
+        &[&admin],
+        blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("compounding within the interval should fail");
+    let expected = u32::from(TreasuryError::CompoundTooSoon);
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, expected, "unexpected error code");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // After the interval: allowed again.
+    context.warp_to_slot(compounded_at + 101).unwrap();
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[compound_ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("compound after the interval should succeed");
+
+    let treasury = fetch_treasury(&mut context.banks_client, treasury_pda).await;
+    assert!(treasury.last_compound_slot > compounded_at);
+}
+
+#[tokio::test]
+async fn test_compound_half_of_yield_into_reserve() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let (treasury_pda, _) = Pubkey::find_program_address(&[b"treasury"], &treasury_engine::id());
+
+    program_test.add_account(
+        treasury_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&Treasury {
+                admin: admin.pubkey(),
+                lp_contributed: 0,
+                co_financing_outstanding: 0,
+                base_fee_accrued: 300,
+                carry_accrued: 100,
+                compounded_xrs: 1_000,
+                paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Xrs,
+                reserve_balance: 0,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
+            }),
+            owner: treasury_engine::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin).await;
+
+    let admin_accounts = treasury_engine::accounts::AdminTreasuryAction {
+        treasury: treasury_pda,
+        admin_authority: admin.pubkey(),
+    };
+    let schedule_ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: admin_accounts.to_account_metas(None),
+        data: treasury_engine::instruction::SetCompoundSchedule {
+            min_compound_interval_slots: 0,
+            compound_rate_bps: 5_000,
+        }
+        .data(),
+    };
+    let destination_ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: admin_accounts.to_account_metas(None),
+        data: treasury_engine::instruction::SetCompoundDestination {
+            destination: CompoundDestination::Reserve,
+        }
+        .data(),
+    };
+    let compound_ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: treasury_engine::accounts::TreasuryCtx {
+            treasury: treasury_pda,
+            authority: admin.pubkey(),
+        }
+        .to_account_metas(None),
+        data: treasury_engine::instruction::TreasuryCompoundXrs {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[schedule_ix, destination_ix, compound_ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("compound into reserve should succeed");
+
+    let treasury = fetch_treasury(&mut context.banks_client, treasury_pda).await;
+    assert_eq!(treasury.compound_rate_bps, 5_000);
+    assert_eq!(treasury.reserve_balance, 200);
+    assert_eq!(treasury.compounded_xrs, 1_000);
+    assert_eq!(treasury.base_fee_accrued, 0);
+    assert_eq!(treasury.carry_accrued, 0);
+}
+
 #[tokio::test]
 async fn test_pause_blocks_allocate() {
     let mut program_test = ProgramTest::new(
@@ -305,6 +661,14 @@ async fn test_pause_blocks_allocate() {
                 carry_accrued: 0,
                 compounded_xrs: 0,
                 paused: false,
+                min_compound_interval_slots: 0,
+                last_compound_slot: 0,
+                compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+                compound_destination: CompoundDestination::Xrs,
+                reserve_balance: 0,
+                protocol_admin: Pubkey::default(),
+                dual_auth_threshold: 0,
+                version: treasury_engine::STATE_VERSION,
             }),
             owner: treasury_engine::id(),
             executable: false,
@@ -374,3 +738,440 @@ async fn test_pause_blocks_allocate() {
     assert!(treasury.paused);
     assert_eq!(treasury.co_financing_outstanding, 0);
 }
+
+fn dual_auth_treasury(admin: Pubkey, protocol_admin: Pubkey, reserve_balance: u64) -> Treasury {
+    Treasury {
+        admin,
+        lp_contributed: 0,
+        co_financing_outstanding: 0,
+        base_fee_accrued: 0,
+        carry_accrued: 0,
+        compounded_xrs: 0,
+        paused: false,
+        min_compound_interval_slots: 0,
+        last_compound_slot: 0,
+        compound_rate_bps: treasury_engine::DEFAULT_COMPOUND_RATE_BPS,
+        compound_destination: CompoundDestination::Reserve,
+        reserve_balance,
+        protocol_admin,
+        dual_auth_threshold: 10_000,
+        version: treasury_engine::STATE_VERSION,
+    }
+}
+
+/// Reserve accounts: the treasury PDA plus its USDC account and a withdrawal destination
+struct ReserveFixture {
+    treasury: Pubkey,
+    usdc_mint: Pubkey,
+    treasury_usdc_account: Pubkey,
+    destination_usdc_account: Pubkey,
+}
+
+fn add_reserve_accounts(program_test: &mut ProgramTest, treasury: Treasury, treasury_usdc: u64) -> ReserveFixture {
+    let (treasury_pda, _) = Pubkey::find_program_address(&[b"treasury"], &treasury_engine::id());
+    let usdc_mint = Pubkey::new_unique();
+    let treasury_usdc_account = Pubkey::new_unique();
+    let destination_usdc_account = Pubkey::new_unique();
+    add_mint_account(program_test, usdc_mint, treasury.admin);
+    add_token_account(program_test, treasury_usdc_account, usdc_mint, treasury_pda, treasury_usdc);
+    add_token_account(program_test, destination_usdc_account, usdc_mint, treasury.admin, 0);
+    program_test.add_account(
+        treasury_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&treasury),
+            owner: treasury_engine::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    ReserveFixture {
+        treasury: treasury_pda,
+        usdc_mint,
+        treasury_usdc_account,
+        destination_usdc_account,
+    }
+}
+
+fn withdraw_reserve_ix(
+    fixture: &ReserveFixture,
+    admin: Pubkey,
+    co_signer: Option<Pubkey>,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: treasury_engine::id(),
+        accounts: treasury_engine::accounts::WithdrawReserve {
+            treasury: fixture.treasury,
+            admin_authority: admin,
+            co_signer,
+            usdc_mint: fixture.usdc_mint,
+            treasury_usdc_account: fixture.treasury_usdc_account,
+            destination_usdc_account: fixture.destination_usdc_account,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: treasury_engine::instruction::TreasuryWithdrawReserve { amount }.data(),
+    }
+}
+
+async fn token_balance(banks_client: &mut BanksClient, address: Pubkey) -> u64 {
+    let account = banks_client
+        .get_account(address)
+        .await
+        .expect("get token account")
+        .expect("token account missing");
+    spl_token::state::Account::unpack(&account.data)
+        .expect("unpack token account")
+        .amount
+}
+
+fn assert_treasury_error(err: BanksClientError, expected: TreasuryError) {
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => assert_eq!(code, u32::from(expected)),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_high_value_withdrawal_requires_both_admins() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let protocol_admin = Keypair::new();
+    let fixture = add_reserve_accounts(
+        &mut program_test,
+        dual_auth_treasury(admin.pubkey(), protocol_admin.pubkey(), 50_000),
+        50_000,
+    );
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin).await;
+
+    // Treasury admin alone cannot move a high-value amount
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_reserve_ix(&fixture, admin.pubkey(), None, 20_000)],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    assert_treasury_error(err, TreasuryError::DualAuthorizationRequired);
+
+    // A co-signer other than the protocol admin does not count
+    let impostor = Keypair::new();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_reserve_ix(&fixture, admin.pubkey(), Some(impostor.pubkey()), 20_000)],
+        Some(&admin.pubkey()),
+        &[&admin, &impostor],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    assert_treasury_error(err, TreasuryError::DualAuthorizationRequired);
+
+    let treasury = fetch_treasury(&mut context.banks_client, fixture.treasury).await;
+    assert_eq!(treasury.reserve_balance, 50_000);
+}
+
+#[tokio::test]
+async fn test_withdraw_reserve_transfers_usdc() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let protocol_admin = Keypair::new();
+    let fixture = add_reserve_accounts(
+        &mut program_test,
+        dual_auth_treasury(admin.pubkey(), protocol_admin.pubkey(), 50_000),
+        50_000,
+    );
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin).await;
+
+    // Below the threshold the treasury admin signs alone
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_reserve_ix(&fixture, admin.pubkey(), None, 5_000)],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.expect("small withdrawal");
+
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_reserve_ix(&fixture, admin.pubkey(), Some(protocol_admin.pubkey()), 20_000)],
+        Some(&admin.pubkey()),
+        &[&admin, &protocol_admin],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.expect("dual-signed withdrawal");
+
+    let treasury = fetch_treasury(&mut context.banks_client, fixture.treasury).await;
+    assert_eq!(treasury.reserve_balance, 25_000);
+    assert_eq!(token_balance(&mut context.banks_client, fixture.treasury_usdc_account).await, 25_000);
+    assert_eq!(token_balance(&mut context.banks_client, fixture.destination_usdc_account).await, 25_000);
+}
+
+#[tokio::test]
+async fn test_withdraw_reserve_rejects_foreign_destination() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let mut fixture = add_reserve_accounts(
+        &mut program_test,
+        dual_auth_treasury(admin.pubkey(), Pubkey::default(), 50_000),
+        50_000,
+    );
+    // Destination holds a different mint than the treasury's USDC
+    let other_mint = Pubkey::new_unique();
+    let foreign_destination = Pubkey::new_unique();
+    add_mint_account(&mut program_test, other_mint, admin.pubkey());
+    add_token_account(&mut program_test, foreign_destination, other_mint, admin.pubkey(), 0);
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin).await;
+
+    fixture.destination_usdc_account = foreign_destination;
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_reserve_ix(&fixture, admin.pubkey(), None, 5_000)],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    assert_treasury_error(err, TreasuryError::InvalidReserveDestination);
+
+    // Nor can the reserve be "withdrawn" back into the treasury's own account
+    fixture.destination_usdc_account = fixture.treasury_usdc_account;
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_reserve_ix(&fixture, admin.pubkey(), None, 6_000)],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    assert_treasury_error(err, TreasuryError::InvalidReserveDestination);
+}
+
+#[tokio::test]
+async fn test_insurance_funding_requires_both_admins() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let protocol_admin = Keypair::new();
+    add_lp_vault_program(&mut program_test);
+    let fixture = add_reserve_accounts(
+        &mut program_test,
+        dual_auth_treasury(admin.pubkey(), protocol_admin.pubkey(), 50_000),
+        50_000,
+    );
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin).await;
+
+    // Above the threshold the treasury admin cannot fund insurance alone
+    let ix = Instruction {
+        program_id: treasury_engine::id(),
+        accounts: treasury_engine::accounts::FundInsuranceFromTreasury {
+            treasury: fixture.treasury,
+            admin_authority: admin.pubkey(),
+            co_signer: None,
+            usdc_mint: fixture.usdc_mint,
+            treasury_usdc_account: fixture.treasury_usdc_account,
+            lp_vault: Pubkey::new_unique(),
+            vault_usdc_account: Pubkey::new_unique(),
+            lp_vault_program: lp_vault::id(),
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: treasury_engine::instruction::FundInsuranceFromTreasury { amount: 20_000 }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], context.last_blockhash);
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    assert_treasury_error(err, TreasuryError::DualAuthorizationRequired);
+
+    let treasury = fetch_treasury(&mut context.banks_client, fixture.treasury).await;
+    assert_eq!(treasury.reserve_balance, 50_000);
+}
+
+#[tokio::test]
+async fn test_dual_authorization_change_needs_protocol_admin() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let protocol_admin = Keypair::new();
+    let (treasury_pda, _) = Pubkey::find_program_address(&[b"treasury"], &treasury_engine::id());
+    program_test.add_account(
+        treasury_pda,
+        Account {
+            lamports: 1_000_000,
+            data: serialize_anchor_account(&dual_auth_treasury(
+                admin.pubkey(),
+                protocol_admin.pubkey(),
+                0,
+            )),
+            owner: treasury_engine::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    fund_signer(&mut context, &admin).await;
+
+    let set_ix = |co_signer: Option<Pubkey>, protocol_admin: Pubkey| Instruction {
+        program_id: treasury_engine::id(),
+        accounts: treasury_engine::accounts::DualAuthTreasuryAction {
+            treasury: treasury_pda,
+            admin_authority: admin.pubkey(),
+            co_signer,
+        }
+        .to_account_metas(None),
+        data: treasury_engine::instruction::SetDualAuthorization {
+            protocol_admin,
+            dual_auth_threshold: 0,
+        }
+        .data(),
+    };
+
+    // The treasury admin cannot switch the check off unilaterally
+    let tx = Transaction::new_signed_with_payer(
+        &[set_ix(None, Pubkey::default())],
+        Some(&admin.pubkey()),
+        &[&admin],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    assert_treasury_error(err, TreasuryError::DualAuthorizationRequired);
+
+    // Both keys must stay distinct
+    let tx = Transaction::new_signed_with_payer(
+        &[set_ix(Some(protocol_admin.pubkey()), admin.pubkey())],
+        Some(&admin.pubkey()),
+        &[&admin, &protocol_admin],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(tx).await.unwrap_err();
+    assert_treasury_error(err, TreasuryError::AdminsNotDistinct);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[set_ix(Some(protocol_admin.pubkey()), Pubkey::default())],
+        Some(&admin.pubkey()),
+        &[&admin, &protocol_admin],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.expect("both admins disable dual auth");
+
+    let treasury = fetch_treasury(&mut context.banks_client, treasury_pda).await;
+    assert_eq!(treasury.protocol_admin, Pubkey::default());
+    assert!(!treasury.requires_dual_auth(u64::MAX));
+}
+
+#[tokio::test]
+async fn test_migrate_treasury_from_pre_version_layout() {
+    let mut program_test = ProgramTest::new(
+        "treasury_engine",
+        treasury_engine::id(),
+        solana_program_test::processor!(treasury_engine_processor),
+    );
+
+    let admin = Keypair::new();
+    let attacker = Keypair::new();
+    let (treasury_pda, _) = Pubkey::find_program_address(&[b"treasury"], &treasury_engine::id());
+    let space = 8 + Treasury::LEN;
+    add_baseline_layout_account::<Treasury, _>(
+        &mut program_test,
+        treasury_pda,
+        &baseline::Treasury {
+            admin: admin.pubkey(),
+            lp_contributed: 1_000_000,
+            co_financing_outstanding: 0,
+            base_fee_accrued: 50_000,
+            carry_accrued: 0,
+            compounded_xrs: 20_000,
+            paused: false,
+        },
+        8 + baseline::Treasury::LEN,
+        space,
+        treasury_engine::id(),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let migrate_ix = |admin_authority: Pubkey| Instruction {
+        program_id: treasury_engine::id(),
+        accounts: treasury_engine::accounts::MigrateTreasuryVersion {
+            treasury: treasury_pda,
+            admin_authority,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: treasury_engine::instruction::MigrateTreasuryVersion {}.data(),
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_ix(attacker.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &attacker],
+        context.last_blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect_err("non-admin should not migrate the treasury");
+    assert_treasury_error(err, TreasuryError::Unauthorized);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_ix(admin.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .expect("admin migrates the treasury");
+
+    let account = context
+        .banks_client
+        .get_account(treasury_pda)
+        .await
+        .unwrap()
+        .expect("treasury account");
+    assert_eq!(account.data.len(), space);
+    let treasury = fetch_treasury(&mut context.banks_client, treasury_pda).await;
+    assert_eq!(treasury.version, treasury_engine::STATE_VERSION);
+    assert_eq!(treasury.admin, admin.pubkey());
+    assert_eq!(treasury.lp_contributed, 1_000_000);
+    assert_eq!(treasury.base_fee_accrued, 50_000);
+    assert_eq!(treasury.compounded_xrs, 20_000);
+    // Fields added since the baseline get initialize_treasury's defaults
+    assert_eq!(treasury.compound_rate_bps, treasury_engine::DEFAULT_COMPOUND_RATE_BPS);
+    assert_eq!(treasury.compound_destination, CompoundDestination::Xrs);
+    assert_eq!(treasury.reserve_balance, 0);
+    assert_eq!(treasury.protocol_admin, Pubkey::default());
+    assert_eq!(treasury.dual_auth_threshold, 0);
+}